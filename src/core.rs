@@ -7,12 +7,14 @@ pub mod database;
 pub mod history;
 pub mod jobtable;
 pub mod options;
+pub mod trap;
 
 use crate::{proc_ctrl, signal};
 use self::database::DataBase;
+use self::history::{HistoryEntry, HistoryFileMark};
 use self::options::Options;
-use std::collections::HashMap;
-use std::os::fd::{FromRawFd, OwnedFd};
+use std::collections::{HashMap, HashSet};
+use std::os::fd::{FromRawFd, OwnedFd, RawFd};
 use std::{io, env, path};
 use nix::{fcntl, unistd};
 use nix::sys::signal::Signal;
@@ -20,7 +22,9 @@ use nix::sys::time::{TimeSpec, TimeVal};
 use nix::unistd::Pid;
 use crate::utils::{error, exit};
 use crate::core::jobtable::JobEntry;
-use std::sync::Arc;
+use signal_hook::iterator::Handle;
+use signal_hook::SigId;
+use std::sync::{Arc, Mutex};
 use std::sync::atomic::AtomicBool;
 
 pub struct MeasuredTime {
@@ -44,10 +48,18 @@ pub struct ShellCore {
     pub db: DataBase,
     pub aliases: HashMap<String, String>,
     pub alias_memo: Vec<(String, String)>,
-    rewritten_history: HashMap<usize, String>,
-    pub history: Vec<String>,
+    pub history: Vec<HistoryEntry>,
+    pub history_base: usize, //number of the old entries dropped by HISTSIZE
+    pub history_file: HistoryFileMark,
     pub builtins: HashMap<String, fn(&mut ShellCore, &mut Vec<String>) -> i32>,
     pub sigint: Arc<AtomicBool>,
+    pub traps: HashMap<String, String>,
+    pub trapped_signals: Arc<Mutex<HashSet<i32>>>,
+    pub caught_signals: Arc<Mutex<Vec<i32>>>,
+    pub signal_handle: Option<Handle>,
+    pub wakeup_pipe: Option<(Pid, RawFd, SigId)>, //owner, read end, handler of SIGCHLD
+    pub wakeup_fd: Arc<Mutex<Option<RawFd>>>,
+    in_trap: bool,
     pub read_stdin: bool,
     pub word_eval_error: bool,
     pub is_subshell: bool,
@@ -59,8 +71,12 @@ pub struct ShellCore {
     pub continue_counter: i32,
     pub return_flag: bool,
     pub tty_fd: Option<OwnedFd>,
+    pub script_fd: Option<RawFd>,
     pub job_table: Vec<JobEntry>,
     pub job_table_priority: Vec<usize>,
+    pub bg_exit_statuses: Vec<(i32, i32)>,
+    pub process_substitutions: Vec<(RawFd, Pid, bool)>,
+    pub process_substitution_pids: Vec<Pid>,
     current_dir: Option<path::PathBuf>, // the_current_working_directory
     pub completion_functions: HashMap<String, String>,
     pub default_completion_functions: String,
@@ -70,6 +86,7 @@ pub struct ShellCore {
     pub options: Options,
     pub shopts: Options,
     pub suspend_e_option: bool,
+    pub exit_warned: bool,
     pub script_name: String,
 }
 
@@ -94,18 +111,19 @@ impl ShellCore {
         let _ = core.db.set_param("PS4", "+ ", None);
 
         if unistd::isatty(0) == Ok(true) {
-            core.db.flags += "i";
+            core.db.flags += "im";
+            core.options.set("monitor", true);
             core.read_stdin = false;
             let _ = core.db.set_param("PS1", "🍣 ", None);
             let _ = core.db.set_param("PS2", "> ", None);
-            let fd = fcntl::fcntl(0, fcntl::F_DUPFD_CLOEXEC(255))
-                .expect("sush(fatal): Can't allocate fd for tty FD");
-            core.tty_fd = Some(unsafe{OwnedFd::from_raw_fd(fd)});
+            core.set_job_control(true);
         }
 
         let home = core.db.get_param("HOME").unwrap_or(String::new()).to_string();
         let _ = core.db.set_param("HISTFILE", &(home + "/.sush_history"), None);
         let _ = core.db.set_param("HISTFILESIZE", "2000", None);
+        let _ = core.db.set_param("HISTSIZE", "2000", None);
+        builtins::option::set_shellopts(&mut core);
 
         core
     }
@@ -129,6 +147,16 @@ impl ShellCore {
         let _ = self.db.set_array("BASH_VERSINFO", versinfo, None);
     }
 
+    pub fn set_job_control(&mut self, on: bool) {
+        if ! on {
+            self.tty_fd = None;
+        }else if self.tty_fd.is_none() && unistd::isatty(0) == Ok(true) {
+            let fd = fcntl::fcntl(0, fcntl::F_DUPFD_CLOEXEC(255))
+                .expect("sush(fatal): Can't allocate fd for tty FD");
+            self.tty_fd = Some(unsafe{OwnedFd::from_raw_fd(fd)});
+        }
+    }
+
     pub fn flip_exit_status(&mut self) {
         self.db.exit_status = if self.db.exit_status == 0 { 1 } else { 0 };
     }
@@ -162,11 +190,12 @@ impl ShellCore {
         signal::restore(Signal::SIGINT);
         signal::restore(Signal::SIGTSTP);
         signal::restore(Signal::SIGPIPE);
+        self.reset_traps_as_subshell();
+        self.signal_handle = None;
 
         self.is_subshell = true;
         proc_ctrl::set_pgid(self, pid, pgid);
         let _ = self.set_subshell_parameters();
-        self.job_table.clear();
     }
 
     pub fn init_current_directory(&mut self) {
@@ -5,9 +5,11 @@
 mod alias;
 mod cd;
 pub mod completion;
+mod echo;
 mod getopts;
 mod history;
 mod job_commands;
+mod mapfile;
 pub mod parameter;
 pub mod option;
 mod printf;
@@ -15,12 +17,15 @@ mod pwd;
 mod read;
 mod source;
 mod loop_control;
+mod test;
+mod trap;
 mod unset;
 
 use crate::{proc_ctrl, Feeder, Script, ShellCore};
 use crate::elements::command::simple::SimpleCommand;
 use crate::elements::io::pipe::Pipe;
-use crate::utils::{arg, error, exit, file};
+use crate::utils::{arg, error, exit, file, file_check};
+use nix::errno::Errno;
 
 impl ShellCore {
     pub fn set_builtins(&mut self) {
@@ -35,17 +40,25 @@ impl ShellCore {
         self.builtins.insert("complete".to_string(), completion::complete);
         self.builtins.insert("continue".to_string(), loop_control::continue_);
         self.builtins.insert("declare".to_string(), parameter::declare);
+        self.builtins.insert("disown".to_string(), job_commands::disown);
+        self.builtins.insert("echo".to_string(), echo::echo);
         self.builtins.insert("eval".to_string(), eval);
+        self.builtins.insert("exec".to_string(), exec);
         self.builtins.insert("exit".to_string(), exit);
+        self.builtins.insert("export".to_string(), parameter::export);
         self.builtins.insert("false".to_string(), false_);
         self.builtins.insert("fg".to_string(), job_commands::fg);
         self.builtins.insert("getopts".to_string(), getopts::getopts);
         self.builtins.insert("history".to_string(), history::history);
         self.builtins.insert("jobs".to_string(), job_commands::jobs);
+        self.builtins.insert("kill".to_string(), job_commands::kill);
         self.builtins.insert("local".to_string(), parameter::local);
+        self.builtins.insert("mapfile".to_string(), mapfile::mapfile);
         self.builtins.insert("printf".to_string(), printf::printf);
         self.builtins.insert("pwd".to_string(), pwd::pwd);
         self.builtins.insert("read".to_string(), read::read);
+        self.builtins.insert("readarray".to_string(), mapfile::mapfile);
+        self.builtins.insert("readonly".to_string(), parameter::readonly);
         self.builtins.insert("return".to_string(), loop_control::return_);
         self.builtins.insert("set".to_string(), option::set);
         self.builtins.insert("shift".to_string(), option::shift);
@@ -53,7 +66,12 @@ impl ShellCore {
         self.builtins.insert("unalias".to_string(), alias::unalias);
         self.builtins.insert("unset".to_string(), unset::unset);
         self.builtins.insert("source".to_string(), source::source);
+        self.builtins.insert("suspend".to_string(), job_commands::suspend);
         self.builtins.insert(".".to_string(), source::source);
+        self.builtins.insert("test".to_string(), test::test);
+        self.builtins.insert("[".to_string(), test::test);
+        self.builtins.insert("trap".to_string(), trap::trap);
+        self.builtins.insert("typeset".to_string(), parameter::declare);
         self.builtins.insert("true".to_string(), true_);
         self.builtins.insert("wait".to_string(), job_commands::wait);
     }
@@ -154,8 +172,73 @@ pub fn eval(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     core.db.exit_status
 }
 
+fn exec_usage(core: &mut ShellCore, msg: &str) -> i32 {
+    error::print(msg, core);
+    error::print("exec: usage: exec [-cl] [-a name] [command [argument ...]] [redirection ...]", core);
+    2
+}
+
+pub fn exec(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (mut clear_env, mut login, mut name) = (false, false, None);
+    let mut pos = 1;
+
+    while pos < args.len() && args[pos].starts_with("-") && args[pos].len() > 1 {
+        if args[pos] == "--" {
+            pos += 1;
+            break;
+        }
+
+        let opt = args[pos].clone();
+        for (i, c) in opt[1..].char_indices() {
+            match c {
+                'c' => clear_env = true,
+                'l' => login = true,
+                'a' => {
+                    name = match (&opt[i+2..], args.get(pos+1)) {
+                        ("", Some(n)) => { pos += 1; Some(n.clone()) },
+                        ("", None) => return exec_usage(core, "exec: -a: option requires an argument"),
+                        (n, _) => Some(n.to_string()),
+                    };
+                    break;
+                },
+                _ => return exec_usage(core, &format!("exec: -{}: invalid option", c)),
+            }
+        }
+        pos += 1;
+    }
+
+    if pos >= args.len() {
+        return 0;
+    }
+
+    let command = args[pos].clone();
+    let mut words = args[pos..].to_vec();
+    if let Some(n) = name {
+        words[0] = n;
+    }
+    if login {
+        words[0] = format!("-{}", &words[0]);
+    }
+
+    let (msg, exit_status) = match proc_ctrl::replace_process(&command, &words, clear_env, core) {
+        Errno::ENOENT => ("not found".to_string(), 127),
+        Errno::EACCES if file_check::is_dir(&command) => ("cannot execute: Is a directory".to_string(), 126),
+        e => (format!("cannot execute: {}", e.desc()), 126),
+    };
+    error::print(&format!("exec: {}: {}", &command, msg), core);
+
+    if core.is_subshell || ! core.db.flags.contains('i') && ! core.shopts.query("execfail") {
+        core.db.exit_status = exit_status;
+        exit::normal(core);
+    }
+    exit_status
+}
+
 pub fn exit(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     eprintln!("exit");
+    if core.jobtable_check_before_exit() {
+        return 1;
+    }
     if args.len() > 1 {
         match &args[1].parse::<i32>() {
             Ok(n) => core.db.exit_status = *n,
@@ -12,7 +12,6 @@ use std::collections::HashSet;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
-use rev_lines::RevLines;
 
 pub fn compgen_f(core: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
     let path = match args.len() {
@@ -209,26 +208,7 @@ fn compgen_d(core: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
 }
 
 pub fn compgen_h(core: &mut ShellCore, _: &mut Vec<String>) -> Vec<String> {
-    let len = core.history.len();
-    if len >= 10 {
-        return core.history[0..10].to_vec();
-    }
-
-    let mut ans = core.history.to_vec();
-
-    if let Ok(hist_file) = File::open(core.db.get_param("HISTFILE").unwrap_or(String::new())){
-        for h in RevLines::new(BufReader::new(hist_file)) {
-            match h {
-                Ok(s) => ans.push(s),
-                _     => {},
-            }
-
-            if ans.len() >= 10 {
-                return ans;
-            }
-        }
-    }
-
+    let mut ans: Vec<String> = core.history.iter().take(10).map(|h| h.text.clone()).collect();
     while ans.len() < 10 {
         ans.push("echo Hello World".to_string());
     }
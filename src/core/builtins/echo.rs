@@ -0,0 +1,54 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::ShellCore;
+use crate::utils::{ansi_c, error, file};
+use crate::utils::ansi_c::Mode;
+
+fn is_option(arg: &str) -> bool {
+    arg.len() > 1 && arg.starts_with("-")
+    && arg[1..].chars().all(|c| "neE".contains(c))
+}
+
+pub fn echo(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut escape = core.shopts.query("xpg_echo");
+    let mut newline = true;
+
+    let mut pos = 1;
+    while pos < args.len() && is_option(&args[pos]) {
+        for c in args[pos][1..].chars() {
+            match c {
+                'n' => newline = false,
+                'e' => escape = true,
+                _   => escape = false,
+            }
+        }
+        pos += 1;
+    }
+
+    let mut out = vec![];
+    for (i, arg) in args[pos..].iter().enumerate() {
+        if i > 0 {
+            out.push(b' ');
+        }
+
+        match escape {
+            true => if ! ansi_c::unescape_to(arg, Mode::Echo, &mut out) {
+                newline = false;
+                break;
+            },
+            false => out.extend(arg.as_bytes()),
+        }
+    }
+
+    if newline {
+        out.push(b'\n');
+    }
+
+    if let Err(e) = file::write_stdout(&out) {
+        let msg = format!("echo: write error: {}", e);
+        error::print(&msg, core);
+        return 1;
+    }
+    0
+}
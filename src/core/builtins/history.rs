@@ -2,32 +2,166 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::ShellCore;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::feeder::history_expansion::{self, Expanded, HistoryMemo};
+use crate::utils::error;
+use super::printf;
 
-pub fn history(core: &mut ShellCore, _: &mut Vec<String>) -> i32 {
-    let mut number = 1;
+fn history_usage(opt: &str, core: &mut ShellCore) -> i32 {
+    error::print(&format!("history: {}: invalid option", opt), core);
+    eprintln!("history: usage: history [-c] [-d offset] [n] or history -anrw [filename] or history -ps arg [arg...]");
+    2
+}
 
-    let filename = core.db.get_param("HISTFILE").unwrap_or(String::new());
-    if filename == "" {
-        return 0;
+fn print(core: &mut ShellCore, num: usize) {
+    let format = match core.db.has_value("HISTTIMEFORMAT") {
+        true  => core.db.get_param("HISTTIMEFORMAT").ok(),
+        false => None,
+    };
+    let last = core.history_base + core.history.len();
+
+    for (i, h) in core.history.iter().take(num).enumerate().rev() {
+        let time = match (&format, h.time) {
+            (Some(f), Some(t)) => printf::strftime(f, t),
+            (Some(_), None)    => "??".to_string(),
+            _ => String::new(),
+        };
+        println!("{:5}  {}{}", last - i, time, h.text.replace("↵ \0", "\n"));
     }
+}
+
+fn delete(core: &mut ShellCore, offset: &str) -> i32 {
+    let len = core.history.len() as i64;
+    let base = core.history_base as i64;
+    let pos = match offset.parse::<i64>() {
+        Ok(n) if n < 0 && len + n >= 0 => len + n,
+        Ok(n) if n > base && n <= base + len => n - base - 1,
+        _ => {
+            let msg = format!("history: {}: history position out of range", offset);
+            error::print(&msg, core);
+            return 1;
+        },
+    };
+
+    core.history.remove((len - 1 - pos) as usize);
+    0
+}
+
+fn file_operation(core: &mut ShellCore, opt: char, args: &[String]) -> i32 {
+    let filename = match args.first() {
+        Some(f) => f.clone(),
+        None    => core.db.get_param("HISTFILE").unwrap_or_default(),
+    };
 
-    let file = match File::open(&filename) {
-        Ok(f) => f,
-        _     => return 0,
+    let result = match opt {
+        'a' => core.write_history(&filename, true),
+        'w' => core.write_history(&filename, false),
+        'r' => core.read_history_file(&filename, false),
+        _   => core.read_history_file(&filename, true),
     };
 
-    let f = BufReader::new(file);
-    for line in f.lines() {
-        println!("{:5} {}", number, &line.unwrap());
-        number += 1;
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            error::print(&format!("history: {}", &e), core);
+            1
+        },
+    }
+}
+
+fn expand(core: &mut ShellCore, args: &[String]) -> i32 {
+    let mut memo = HistoryMemo::default();
+    for arg in args {
+        match history_expansion::expand(arg, &mut memo, core) {
+            Ok(Expanded::Changed(s)) | Ok(Expanded::PrintOnly(s)) => println!("{}", &s),
+            Ok(Expanded::Unchanged) => println!("{}", &arg),
+            Err(e) => {
+                error::print(&e, core);
+                return 1;
+            },
+        }
+    }
+    0
+}
+
+pub fn history(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut opts = String::new();
+    let mut offset = None;
+    let mut pos = 1;
+
+    while pos < args.len() && args[pos].starts_with("-") && args[pos].len() > 1 {
+        if args[pos] == "--" {
+            pos += 1;
+            break;
+        }
+        let opt = args[pos].clone();
+        for (i, c) in opt[1..].chars().enumerate() {
+            match c {
+                'c' | 'a' | 'n' | 'r' | 'w' | 'p' | 's' => opts.push(c),
+                'd' => {
+                    offset = match (&opt[i+2..], args.get(pos+1)) {
+                        ("", Some(a)) => { pos += 1; Some(a.clone()) },
+                        ("", None) => {
+                            error::print("history: -d: option requires an argument", core);
+                            return 2;
+                        },
+                        (s, _) => Some(s.to_string()),
+                    };
+                    break;
+                },
+                _ => return history_usage(&format!("-{}", c), core),
+            }
+        }
+        pos += 1;
+    }
+    let rest = &args[pos..];
+
+    if opts.contains('c') {
+        core.clear_history();
+    }
+    if let Some(offset) = &offset {
+        if delete(core, offset) != 0 {
+            return 1;
+        }
+    }
+
+    let file_opts: Vec<char> = opts.chars().filter(|c| "anrw".contains(*c)).collect();
+    if file_opts.len() > 1 {
+        error::print("history: cannot use more than one of -anrw", core);
+        return 1;
+    }
+    if let Some(opt) = file_opts.first() {
+        return file_operation(core, *opt, rest);
     }
 
-    for h in core.history.iter().rev() {
-        println!("{:5} {}", number, &h);
-        number += 1;
+    if opts.contains('p') {
+        return expand(core, rest);
+    }
+    if opts.contains('s') {
+        if ! rest.is_empty() {
+            core.add_history(&rest.join(" "));
+        }
+        return 0;
     }
+    if opts.contains('c') || offset.is_some() {
+        return 0;
+    }
+
+    let num = match rest.len() {
+        0 => core.history.len(),
+        1 => match rest[0].parse::<i64>() {
+            Ok(n) => std::cmp::max(n, 0) as usize,
+            Err(_) => {
+                let msg = format!("history: {}: numeric argument required", &rest[0]);
+                error::print(&msg, core);
+                return 1;
+            },
+        },
+        _ => {
+            error::print("history: too many arguments", core);
+            return 1;
+        },
+    };
 
+    print(core, num);
     0
 }
@@ -1,11 +1,11 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::ShellCore;
+use crate::{utils, ShellCore};
 use crate::core::JobEntry;
 use crate::signal;
 use crate::utils::error;
-use nix::sys::signal::Signal;
+use nix::sys::signal::{kill as send_signal, Signal};
 use nix::unistd;
 use nix::unistd::Pid;
 
@@ -20,9 +20,9 @@ fn id_to_job(id: usize, jobs: &mut Vec<JobEntry>) -> Option<&mut JobEntry> {
 }
 
 fn arg_to_id(s: &str, priority: &Vec<usize>, table: &Vec<JobEntry>) -> Result<usize, String> {
-    if s == "%+" {
+    if s == "%+" || s == "%%" || s == "%" {
         return match priority.len() {
-            0 => Err("%+: no such job".to_string()), 
+            0 => Err(format!("{}: no such job", s)),
             _ => Ok(priority[0]),
         };
     }
@@ -36,10 +36,25 @@ fn arg_to_id(s: &str, priority: &Vec<usize>, table: &Vec<JobEntry>) -> Result<us
     }
 
     let word = &s[1..];
+    if let Ok(n) = word.parse::<usize>() {
+        return match s.starts_with("%") && table.iter().any(|j| j.id == n) {
+            true  => Ok(n),
+            false => Err(format!("{}: no such job", s)),
+        };
+    }
+
+    let (word, contains) = match word.strip_prefix("?") {
+        Some(w) => (w, true),
+        None    => (word, false),
+    };
+
     let mut ans = 0;
     for job in table {
-        let jobname = job.text.split(" ").nth(0).unwrap();
-        if jobname == word {
+        let matched = match contains {
+            true  => job.text.contains(word),
+            false => job.text.trim_start().starts_with(word),
+        };
+        if matched {
             if ans != 0 {
                 return Err((s.to_owned() + ": ambiguous job spec").to_string());
             }
@@ -47,17 +62,10 @@ fn arg_to_id(s: &str, priority: &Vec<usize>, table: &Vec<JobEntry>) -> Result<us
         }
     }
 
-    if ans != 0 {
+    if ans != 0 && s.starts_with("%") {
         return Ok(ans);
     }
 
-    if s.starts_with("%") {
-        return match word.parse::<usize>() {
-            Ok(n)  => Ok(n),
-            Err(_) => Err((s.to_owned() + ": no such job").to_string()),
-        };
-    }
-
     Err((s.to_owned() + ": no such job").to_string())
 }
 
@@ -136,32 +144,464 @@ pub fn fg(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     exit_status
 }
 
-pub fn jobs(core: &mut ShellCore, _: &mut Vec<String>) -> i32 {
-    for job in core.job_table.iter() {
-        job.print(&core.job_table_priority);
+pub fn jobs(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (mut long, mut pid_only, mut changed, mut running, mut stopped) = (false, false, false, false, false);
+    let mut pos = 1;
+
+    while pos < args.len() && args[pos].starts_with("-") && args[pos].len() > 1 {
+        if args[pos] == "--" {
+            pos += 1;
+            break;
+        }
+
+        for c in args[pos][1..].chars() {
+            match c {
+                'l' => long = true,
+                'p' => pid_only = true,
+                'n' => changed = true,
+                'r' => running = true,
+                's' => stopped = true,
+                _ => {
+                    let msg = format!("jobs: -{}: invalid option", c);
+                    error::print(&msg, core);
+                    error::print("jobs: usage: jobs [-lnprs] [jobspec ...] or jobs -x command [args]", core);
+                    return 2;
+                },
+            }
+        }
+        pos += 1;
+    }
+
+    core.jobtable_check_status();
+
+    let mut exit_status = 0;
+    let mut ids = vec![];
+    for arg in &args[pos..] {
+        match arg_to_id(arg, &core.job_table_priority, &core.job_table) {
+            Ok(id) => ids.push(id),
+            Err(e) => {
+                error::print(&format!("jobs: {}", e), core);
+                exit_status = 1;
+            },
+        }
+    }
+    if pos >= args.len() {
+        ids = core.job_table.iter().map(|j| j.id).collect();
+    }
+
+    for id in ids {
+        let job = match id_to_job(id, &mut core.job_table) {
+            Some(job) => job,
+            None => continue,
+        };
+        if (running && ! job.is_running()) || (stopped && ! job.is_stopped()) {
+            continue;
+        }
+        if ! job.take_change() && changed {
+            continue;
+        }
+
+        if pid_only {
+            println!("{}", job.pids()[0]);
+        }else if long {
+            job.print_with_pids(&core.job_table_priority);
+        }else{
+            job.print(&core.job_table_priority);
+        }
+    }
+    exit_status
+}
+
+fn wait_usage(core: &mut ShellCore, msg: &str) -> i32 {
+    error::print(msg, core);
+    error::print("wait: usage: wait [-fn] [-p var] [id ...]", core);
+    2
+}
+
+fn trapped_signal_status(core: &mut ShellCore) -> Option<i32> {
+    core.caught_signals.lock().unwrap().first().map(|n| n + 128)
+}
+
+fn wait_job(core: &mut ShellCore, id: usize, pid: Option<i32>, force: bool) -> (i32, i32) {
+    loop {
+        signal::prepare_wakeup(core);
+        if let Some(es) = trapped_signal_status(core) {
+            return (es, 0);
+        }
+        let job = match id_to_job(id, &mut core.job_table) {
+            Some(job) if ! job.is_inherited() => job,
+            _ => return (127, 0),
+        };
+
+        job.update_status(false);
+        if job.is_done() || (job.is_stopped() && ! force) {
+            break;
+        }
+        signal::wait_wakeup(core);
+    }
+
+    let job = id_to_job(id, &mut core.job_table).unwrap();
+    let mut exit_status = match job.is_stopped() {
+        true  => 148,
+        false => job.exit_status(),
+    };
+    let pid = pid.unwrap_or(job.pids().last().map(|p| p.as_raw()).unwrap_or(0));
+    if let (false, Some(es)) = (job.is_stopped(), job.pid_status(pid)) {
+        exit_status = es;
+    }
+
+    if job.is_done() {
+        core.jobtable_remove(id);
+    }
+    (exit_status, pid)
+}
+
+fn wait_arg(core: &mut ShellCore, arg: &str, force: bool) -> Result<(i32, i32), (String, i32)> {
+    if arg.starts_with("%") {
+        return match arg_to_id(arg, &core.job_table_priority, &core.job_table) {
+            Ok(id) => Ok(wait_job(core, id, None, force)),
+            Err(e) => Err((e, 127)),
+        };
+    }
+
+    let pid = match arg.parse::<i32>() {
+        Ok(pid) => pid,
+        Err(_) => return Err((format!("`{}': not a pid or valid job spec", arg), 1)),
+    };
+
+    if let Some(job) = core.job_table.iter().find(|j| j.has_pid(pid)) {
+        let id = job.id;
+        return Ok(wait_job(core, id, Some(pid), force));
+    }
+
+    match core.take_bg_exit_status(pid) {
+        Some(es) => Ok((es, pid)),
+        None => Err((format!("pid {} is not a child of this shell", pid), 127)),
+    }
+}
+
+fn wait_next(core: &mut ShellCore, args: &[String], force: bool) -> (i32, i32) {
+    let mut ids = vec![];
+    for arg in args {
+        if let Ok(pid) = arg.parse::<i32>() {
+            if let Some(job) = core.job_table.iter().find(|j| j.has_pid(pid)) {
+                ids.push(job.id);
+            }
+        }else if let Ok(id) = arg_to_id(arg, &core.job_table_priority, &core.job_table) {
+            ids.push(id);
+        }
+    }
+    if args.is_empty() {
+        ids = core.job_table.iter().map(|j| j.id).collect();
+    }
+
+    loop {
+        signal::prepare_wakeup(core);
+        if let Some(es) = trapped_signal_status(core) {
+            return (es, 0);
+        }
+        let mut waiting = false;
+        for id in &ids {
+            let job = match id_to_job(*id, &mut core.job_table) {
+                Some(job) if ! job.is_inherited() => job,
+                _ => continue,
+            };
+            job.update_status(false);
+            if job.is_done() {
+                return wait_job(core, *id, None, force);
+            }
+            waiting |= force || ! job.is_stopped();
+        }
+
+        if ! waiting {
+            return (127, 0);
+        }
+        signal::wait_wakeup(core);
     }
-    0
 }
 
 pub fn wait(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    if args.len() <= 1 {
-        for job in core.job_table.iter_mut() {
-            job.update_status(true);
+    let (mut next, mut force, mut var) = (false, false, None);
+    let mut pos = 1;
+
+    while pos < args.len() && args[pos].starts_with("-") && args[pos].len() > 1 {
+        if args[pos] == "--" {
+            pos += 1;
+            break;
+        }
+
+        let arg = args[pos].clone();
+        for (i, c) in arg[1..].char_indices() {
+            match c {
+                'n' => next = true,
+                'f' => force = true,
+                'p' => {
+                    var = match (&arg[i+2..], args.get(pos+1)) {
+                        ("", Some(v)) => { pos += 1; Some(v.clone()) },
+                        ("", None) => return wait_usage(core, "wait: -p: option requires an argument"),
+                        (v, _) => Some(v.to_string()),
+                    };
+                    break;
+                },
+                _ => return wait_usage(core, &format!("wait: -{}: invalid option", c)),
+            }
         }
-        return 0;
+        pos += 1;
     }
 
-    let id = match arg_to_id(&args[1], &core.job_table_priority, &core.job_table) {
-        Ok(n)  => n,
-        Err(s) => {
-            error::print(&("wait: ".to_owned() + &s), core);
+    if let Some(v) = &var {
+        if ! utils::is_param(v) {
+            error::print(&format!("wait: `{}': not a valid identifier", v), core);
             return 1;
-        },
+        }
+        core.db.unset(v);
+    }
+
+    let mut exit_status = 0;
+    let mut pid = 0;
+    if next {
+        (exit_status, pid) = wait_next(core, &args[pos..], force);
+    }else if pos >= args.len() {
+        let ids = core.job_table.iter().map(|j| j.id).collect::<Vec<usize>>();
+        for id in ids {
+            wait_job(core, id, None, force);
+            if let Some(es) = trapped_signal_status(core) {
+                return es;
+            }
+        }
+        core.bg_exit_statuses.clear();
+    }else{
+        for arg in args[pos..].iter() {
+            match wait_arg(core, arg, force) {
+                Ok((es, p)) => (exit_status, pid) = (es, p),
+                Err((msg, es)) => {
+                    error::print(&format!("wait: {}", msg), core);
+                    (exit_status, pid) = (es, 0);
+                },
+            }
+            if trapped_signal_status(core).is_some() {
+                break;
+            }
+        }
+    }
+
+    if let (Some(v), true) = (&var, pid > 0) {
+        if let Err(e) = core.db.set_param(v, &pid.to_string(), None) {
+            error::print(&format!("wait: {}", e), core);
+            return 1;
+        }
+    }
+    exit_status
+}
+
+fn kill_usage(core: &mut ShellCore) -> i32 {
+    let msg = "kill: usage: kill [-s sigspec | -n signum | -sigspec] pid | jobspec ... or kill -l [sigspec]";
+    error::print(msg, core);
+    2
+}
+
+fn to_kill_signal(s: &str) -> Option<Option<Signal>> {
+    match s {
+        "0" | "EXIT" | "SIGEXIT" => Some(None),
+        _ => signal::to_signal(s).map(Some),
+    }
+}
+
+fn kill_list(core: &mut ShellCore, args: &[String]) -> i32 {
+    if args.is_empty() {
+        return super::trap::print_signal_list();
+    }
+
+    let mut exit_status = 0;
+    for arg in args {
+        if let Ok(n) = arg.parse::<i32>() {
+            let n = if n > 128 { n - 128 } else { n };
+            match n {
+                0 => println!("EXIT"),
+                _ => match Signal::try_from(n) {
+                    Ok(sig) => println!("{}", &sig.as_str()[3..]),
+                    Err(_) => exit_status = invalid_signal(core, arg),
+                },
+            }
+            continue;
+        }
+
+        match to_kill_signal(arg) {
+            Some(Some(sig)) => println!("{}", sig as i32),
+            Some(None) => println!("0"),
+            None => exit_status = invalid_signal(core, arg),
+        }
+    }
+    exit_status
+}
+
+fn invalid_signal(core: &mut ShellCore, name: &str) -> i32 {
+    let msg = format!("kill: {}: invalid signal specification", name);
+    error::print(&msg, core);
+    1
+}
+
+fn kill_target(core: &mut ShellCore, target: &str, sig: Option<Signal>) -> Result<(), String> {
+    if target.starts_with("%") {
+        let id = arg_to_id(target, &core.job_table_priority, &core.job_table)?;
+        return match id_to_job(id, &mut core.job_table) {
+            Some(job) => job.send_signal(sig),
+            None => Err(format!("{}: no such job", target)),
+        };
+    }
+
+    let pid = match target.parse::<i32>() {
+        Ok(n) => n,
+        Err(_) => return Err(format!("{}: arguments must be process or job IDs", target)),
     };
-    match id_to_job(id, &mut core.job_table) {
-        Some(job) => {job.update_status(true);},
-        _ => return 1, 
+
+    send_signal(Pid::from_raw(pid), sig)
+        .map_err(|e| format!("({}) - {}", pid, e.desc()))
+}
+
+pub fn kill(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut sig = Some(Signal::SIGTERM);
+    let mut pos = 1;
+
+    if pos < args.len() && (args[pos] == "-l" || args[pos] == "-L") {
+        return kill_list(core, &args[pos+1..]);
+    }
+
+    if pos < args.len() && args[pos].starts_with("-") && args[pos].len() > 1 {
+        let spec = match args[pos].as_str() {
+            "--" => None,
+            opt @ ("-s" | "-n") => match args.get(pos+1) {
+                Some(s) => {
+                    pos += 1;
+                    Some(s.clone())
+                },
+                None => {
+                    let msg = format!("kill: {}: option requires an argument", opt);
+                    error::print(&msg, core);
+                    return 1;
+                },
+            },
+            opt => Some(opt[1..].to_string()),
+        };
+
+        if let Some(spec) = spec {
+            sig = match to_kill_signal(&spec) {
+                Some(s) => s,
+                None => return invalid_signal(core, &spec),
+            };
+        }
+        pos += 1;
     }
 
+    if pos >= args.len() {
+        return kill_usage(core);
+    }
+
+    let mut exit_status = 0;
+    for target in &args[pos..] {
+        if let Err(e) = kill_target(core, target, sig) {
+            let msg = format!("kill: {}", e);
+            error::print(&msg, core);
+            exit_status = 1;
+        }
+    }
+    exit_status
+}
+
+fn disown_targets(core: &mut ShellCore, targets: &[String]) -> Result<Vec<usize>, String> {
+    if targets.is_empty() {
+        return match core.job_table_priority.first() {
+            Some(id) => Ok(vec![*id]),
+            None => Err("current: no such job".to_string()),
+        };
+    }
+
+    let mut ans = vec![];
+    for t in targets {
+        if let Ok(pid) = t.parse::<i32>() {
+            match core.job_table.iter().find(|j| j.has_pid(pid)) {
+                Some(job) => ans.push(job.id),
+                None => return Err(format!("{}: no such job", t)),
+            }
+            continue;
+        }
+        ans.push(arg_to_id(t, &core.job_table_priority, &core.job_table)?);
+    }
+    Ok(ans)
+}
+
+pub fn disown(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (mut all, mut running, mut no_hup) = (false, false, false);
+    let mut pos = 1;
+
+    while pos < args.len() && args[pos].starts_with("-") && args[pos].len() > 1 {
+        if args[pos] == "--" {
+            pos += 1;
+            break;
+        }
+
+        for c in args[pos][1..].chars() {
+            match c {
+                'a' => all = true,
+                'r' => running = true,
+                'h' => no_hup = true,
+                _ => {
+                    let msg = format!("disown: -{}: invalid option", c);
+                    error::print(&msg, core);
+                    error::print("disown: usage: disown [-h] [-ar] [jobspec ... | pid ...]", core);
+                    return 2;
+                },
+            }
+        }
+        pos += 1;
+    }
+
+    let ids = if all || (running && pos >= args.len()) {
+        core.job_table.iter()
+            .filter(|j| ! running || j.is_running())
+            .map(|j| j.id).collect()
+    }else{
+        match disown_targets(core, &args[pos..]) {
+            Ok(ids) => ids,
+            Err(e) => {
+                error::print(&format!("disown: {}", e), core);
+                return 1;
+            },
+        }
+    };
+
+    for id in ids {
+        if no_hup {
+            if let Some(job) = id_to_job(id, &mut core.job_table) {
+                job.no_hup = true;
+            }
+            continue;
+        }
+
+        core.jobtable_remove(id);
+    }
     0
 }
+
+pub fn suspend(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let force = match args.get(1).map(|s| s.as_str()) {
+        None => false,
+        Some("-f") => true,
+        Some(opt) => {
+            let msg = format!("suspend: {}: invalid option", opt);
+            error::print(&msg, core);
+            error::print("suspend: usage: suspend [-f]", core);
+            return 2;
+        },
+    };
+
+    if core.shopts.query("login_shell") && ! force {
+        error::print("suspend: cannot suspend a login shell", core);
+        return 1;
+    }
+
+    match send_signal(unistd::getpid(), Signal::SIGSTOP) {
+        Ok(()) => 0,
+        Err(_) => 1,
+    }
+}
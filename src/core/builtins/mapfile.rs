@@ -0,0 +1,183 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::ShellCore;
+use crate::utils::{ansi_c, error};
+use crate::utils::ansi_c::Quote;
+use nix::{fcntl, unistd};
+use std::os::fd::RawFd;
+
+#[derive(Debug)]
+struct Options {
+    name: String,
+    delim: u8,
+    count: usize,
+    origin: Option<usize>,
+    skip: usize,
+    fd: RawFd,
+    trim: bool,
+    callback: Option<String>,
+    quantum: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            name: "MAPFILE".to_string(),
+            delim: b'\n',
+            count: 0,
+            origin: None,
+            skip: 0,
+            fd: 0,
+            trim: false,
+            callback: None,
+            quantum: 5000,
+        }
+    }
+}
+
+fn to_number(com: &str, s: &str, msg: &str) -> Result<usize, String> {
+    s.parse::<usize>().map_err(|_| format!("{}: {}: {}", com, s, msg))
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let com = &args[0];
+    let mut opts = Options::default();
+    let mut pos = 1;
+
+    while pos < args.len() && args[pos].starts_with("-") && args[pos].len() > 1 {
+        if args[pos] == "--" {
+            pos += 1;
+            break;
+        }
+
+        let arg = args[pos].clone();
+        for (i, c) in arg[1..].char_indices() {
+            if c == 't' {
+                opts.trim = true;
+                continue;
+            }
+
+            if ! "dnOsuCc".contains(c) {
+                return Err(format!("{}: -{}: invalid option", com, c));
+            }
+
+            let value = match &arg[i+2..] {
+                "" => {
+                    pos += 1;
+                    match args.get(pos) {
+                        Some(v) => v.clone(),
+                        None => return Err(format!("{}: -{}: option requires an argument", com, c)),
+                    }
+                },
+                rest => rest.to_string(),
+            };
+
+            match c {
+                'd' => opts.delim = value.bytes().next().unwrap_or(0),
+                'n' => opts.count = to_number(com, &value, "invalid line count")?,
+                'O' => opts.origin = Some(to_number(com, &value, "invalid array origin")?),
+                's' => opts.skip = to_number(com, &value, "invalid line count")?,
+                'u' => opts.fd = to_number(com, &value, "invalid file descriptor specification")? as RawFd,
+                'C' => opts.callback = Some(value),
+                _ => match to_number(com, &value, "invalid callback quantum")? {
+                    0 => return Err(format!("{}: {}: invalid callback quantum", com, value)),
+                    n => opts.quantum = n,
+                },
+            }
+            break;
+        }
+        pos += 1;
+    }
+
+    if pos < args.len() {
+        opts.name = args[pos].clone();
+    }
+    Ok(opts)
+}
+
+fn read_line(fd: RawFd, delim: u8, trim: bool) -> Option<String> {
+    let mut line = vec![];
+    let mut ch = [0; 1];
+
+    loop {
+        match unistd::read(fd, &mut ch) {
+            Ok(1) => {
+                if ch[0] == delim {
+                    if ! trim {
+                        line.push(ch[0]);
+                    }
+                    break;
+                }
+                line.push(ch[0]);
+            },
+            _ if line.is_empty() => return None,
+            _ => break,
+        }
+    }
+
+    Some(String::from_utf8_lossy(&line).to_string())
+}
+
+fn call_back(core: &mut ShellCore, callback: &str, index: usize, line: &str) {
+    let com = format!("{} {} {}", callback, index, ansi_c::quote(line, Quote::Single));
+    let mut args = vec!["eval".to_string(), com];
+    super::eval(core, &mut args);
+}
+
+fn mapfile_(core: &mut ShellCore, opts: &Options) -> Result<(), String> {
+    if fcntl::fcntl(opts.fd, fcntl::F_GETFD).is_err() {
+        return Err(format!("{}: invalid file descriptor: Bad file descriptor", opts.fd));
+    }
+
+    let origin = match opts.origin {
+        Some(n) => n,
+        None => {
+            core.db.set_array(&opts.name, vec![], None)?;
+            0
+        },
+    };
+
+    for _ in 0..opts.skip {
+        if read_line(opts.fd, opts.delim, opts.trim).is_none() {
+            return Ok(());
+        }
+    }
+
+    let mut num = 0;
+    while opts.count == 0 || num < opts.count {
+        let line = match read_line(opts.fd, opts.delim, opts.trim) {
+            Some(line) => line,
+            None => break,
+        };
+
+        if let Some(callback) = &opts.callback {
+            if (num + 1) % opts.quantum == 0 {
+                call_back(core, callback, origin + num, &line);
+            }
+        }
+
+        core.db.set_array_elem(&opts.name, &line, origin + num, None)?;
+        num += 1;
+    }
+    Ok(())
+}
+
+pub fn mapfile(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let opts = match parse_options(args) {
+        Ok(opts) => opts,
+        Err(e) => {
+            error::print(&e, core);
+            return 2;
+        },
+    };
+
+    match mapfile_(core, &opts) {
+        Ok(()) => 0,
+        Err(e) => {
+            let msg = format!("{}: {}", &args[0], e);
+            error::print(&msg, core);
+            1
+        },
+    }
+}
@@ -2,9 +2,23 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::ShellCore;
+use crate::core::options::FLAG_OPTIONS;
 use crate::utils::{arg, error};
 use super::parameter;
 
+fn long_name(opt: char) -> Option<&'static str> {
+    FLAG_OPTIONS.iter().find(|f| f.0 == opt).map(|f| f.1)
+}
+
+fn short_name(name: &str) -> Option<char> {
+    FLAG_OPTIONS.iter().find(|f| f.1 == name).map(|f| f.0)
+}
+
+pub fn set_shellopts(core: &mut ShellCore) {
+    let opts = core.options.get_enabled().join(":");
+    let _ = core.db.set_param("SHELLOPTS", &opts, Some(0));
+}
+
 fn set_option(core: &mut ShellCore, opt: char, pm: char) {
     if pm == '+' {
         core.db.flags.retain(|e| e != opt);
@@ -13,33 +27,59 @@ fn set_option(core: &mut ShellCore, opt: char, pm: char) {
             core.db.flags.push(opt);
         }
     }
+
+    if let Some(name) = long_name(opt) {
+        core.options.set(name, pm == '-');
+    }
+    if opt == 'm' {
+        core.set_job_control(pm == '-');
+    }
+    set_shellopts(core);
+}
+
+fn set_long_option(core: &mut ShellCore, name: &str, pm: char) -> i32 {
+    if let Some(opt) = short_name(name) {
+        set_option(core, opt, pm);
+        return 0;
+    }
+
+    if ! core.options.exist(name) {
+        let msg = format!("set: {}: invalid option name", name);
+        error::print(&msg, core);
+        return 2;
+    }
+
+    core.options.set(name, pm == '-');
+    set_shellopts(core);
+    0
 }
 
 pub fn set_options(core: &mut ShellCore, args: &[String]) -> i32 {
     for a in args {
-        if a.len() != 2 {
+        if a.len() < 2 {
             error::internal("invalid option");
             return 1;
         }
 
-        let pm = a.chars().nth(0).unwrap();
-        let ch = a.chars().nth(1).unwrap();
-
+        let pm = a.chars().next().unwrap();
         if pm != '-' && pm != '+' {
             error::internal("not an option");
             return 1;
-        }else if "xveB".find(ch).is_none() {
-            eprintln!("sush: set: {}: invalid option", &a);
-            return 2;
         }
 
-        set_option(core, ch, pm);
+        for ch in a[1..].chars() {
+            if long_name(ch).is_none() {
+                eprintln!("sush: set: {}{}: invalid option", pm, ch);
+                return 2;
+            }
+            set_option(core, ch, pm);
+        }
     }
     0
 }
 
 pub fn set(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    let mut args = arg::dissolve_options(args);
+    let args = arg::dissolve_options(args);
 
     if args.is_empty() {
         panic!("never come here");
@@ -47,33 +87,36 @@ pub fn set(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
         return parameter::print_all(core);
     }
 
-    if args[1].starts_with("--") {
-        args.remove(0);
-        return parameter::set_positions(core, &args)
-    }
-
-    if args[1] == "-o" || args[1] == "+o" {
-        let positive = args[1] == "-o";
+    let mut pos = 1;
+    while pos < args.len() {
+        let arg = args[pos].clone();
+        if arg == "-o" || arg == "+o" {
+            if pos + 1 == args.len() {
+                core.options.print_all(arg == "-o");
+                return 0;
+            }
 
-        if args.len() == 2 {
-            core.options.print_all(positive);
-            return 0;
-        }else{
-            if args[2] == "noglob" {
-                eprintln!("{}: not supprted yet", &args[2]);
-                return 1;
+            let pm = arg.chars().next().unwrap();
+            let ans = set_long_option(core, &args[pos+1], pm);
+            if ans != 0 {
+                return ans;
             }
-            return match core.options.set(&args[2], positive) {
-                true  => 0,
-                false => 2,
-            };
+            pos += 2;
+        }else if arg == "--" {
+            let params = [&args[..1], &args[pos+1..]].concat();
+            return parameter::set_positions(core, &params);
+        }else if arg.starts_with("-") || arg.starts_with("+") {
+            let ans = set_options(core, &args[pos..pos+1]);
+            if ans != 0 {
+                return ans;
+            }
+            pos += 1;
+        }else{
+            let params = [&args[..1], &args[pos..]].concat();
+            return parameter::set_positions(core, &params);
         }
     }
-
-    match args[1].starts_with("-") || args[1].starts_with("+") {
-        true  => set_options(core, &args[1..]),
-        false => parameter::set_positions(core, &args),
-    }
+    0
 }
 
 pub fn shift(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
@@ -144,7 +187,7 @@ pub fn shopt(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
 
     let res = match args[1].as_str() {
         "-s" => {
-            if ["extglob", "progcomp"].iter().any(|&e| e == args[2]) {
+            if ["checkjobs", "execfail", "extglob", "histreedit", "histshare", "histverify", "huponexit", "nocasematch", "progcomp", "xpg_echo"].iter().any(|&e| e == args[2]) {
                 core.shopts.set(&args[2], true)
             }else{
                 let msg = format!("shopt: {}: not supported yet", &args[2]);
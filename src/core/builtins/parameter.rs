@@ -2,10 +2,10 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{ShellCore, Feeder};
-use crate::utils;
 use crate::utils::exit;
 use crate::elements::substitution::Substitution;
-use crate::utils::{arg, error};
+use crate::utils::error;
+use std::env;
 
 pub fn set_positions(core: &mut ShellCore, args: &[String]) -> i32 {
     match core.db.position_parameters.pop() {
@@ -28,42 +28,147 @@ pub fn print_all(core: &mut ShellCore) -> i32 {
     0
 }
 
-fn set_local(arg: &str, core: &mut ShellCore, layer: usize) -> Result<(), String> {
-    let mut feeder = Feeder::new(arg);
-    if feeder.scanner_name(core) == feeder.len() { // name only
-        let name = feeder.consume(feeder.len());
-        return core.db.set_param(&name, "", Some(layer));
+fn split_options(args: &[String]) -> (String, String, Vec<String>) {
+    let mut flags = String::new();
+    let mut unflags = String::new();
+
+    for (i, a) in args.iter().enumerate() {
+        if a == "--" {
+            return (flags, unflags, args[i+1..].to_vec());
+        }
+        if a.len() < 2 || a.contains('=') {
+            return (flags, unflags, args[i..].to_vec());
+        }
+        match a.chars().next() {
+            Some('-') => flags += &a[1..],
+            Some('+') => unflags += &a[1..],
+            _ => return (flags, unflags, args[i..].to_vec()),
+        }
     }
+    (flags, unflags, vec![])
+}
 
+fn set_value(arg: &str, core: &mut ShellCore, layer: Option<usize>, com: &str) -> Result<(), String> {
+    let mut feeder = Feeder::new(arg);
     let mut sub = match Substitution::parse(&mut feeder, core) {
         Some(s) => s,
-        _ => return Err(format!("local: `{}': not a valid identifier", arg)),
+        _ => return Err(format!("{}: `{}': not a valid identifier", com, arg)),
     };
 
-    match sub.eval(core, Some(layer), false) {
+    match sub.eval(core, layer, false) {
         true  => Ok(()),
-        false => Err(format!("local: `{}': evaluation error", arg)),
+        false => Err(format!("{}: `{}': evaluation error", com, arg)),
     }
 }
 
-fn set_local_array(arg: &str, core: &mut ShellCore, layer: usize) -> Result<(), String> {
+fn declare_name(arg: &str, core: &mut ShellCore, layer: Option<usize>,
+                flags: &str, unflags: &str, com: &str) -> Result<(), String> {
     let mut feeder = Feeder::new(arg);
-    if feeder.scanner_name(core) == feeder.len() { // name only
-        let name = feeder.consume(feeder.len());
-        return core.db.set_array(&name, vec![], Some(layer));
+    let len = feeder.scanner_name(core);
+    if len == 0 {
+        return Err(format!("{}: `{}': not a valid identifier", com, arg));
     }
+    let name = feeder.consume(len);
+    let name_only = feeder.len() == 0;
 
-    let mut sub = match Substitution::parse(&mut feeder, core) {
-        Some(s) => s,
-        _ => {
-            return Err(format!("local: `{}': not a valid identifier", arg));
-        },
-    };
+    if flags.contains('n') {
+        core.db.unset_flag(&name, 'n');
+    }
 
-    match sub.eval(core, Some(layer), false) {
-        true  => Ok(()),
-        false => Err(format!("local: `{}': evaluation error", arg)),
+    if flags.contains('A') {
+        if ! core.db.is_assoc(&name) {
+            core.db.set_assoc(&name, layer)?;
+        }
+    }else if flags.contains('a') {
+        if name_only && ! core.db.is_array(&name) {
+            core.db.set_array(&name, vec![], layer)?;
+        }
+    }else if let (Some(n), true) = (layer, name_only) {
+        if core.db.get_layer_pos(&name) != Some(n) {
+            core.db.set_param(&name, "", Some(n))?;
+        }
+    }
+
+    for f in flags.chars().filter(|f| "ilu".contains(*f)) {
+        match f {
+            'l' => core.db.unset_flag(&name, 'u'),
+            'u' => core.db.unset_flag(&name, 'l'),
+            _   => {},
+        }
+        core.db.set_flag(&name, f);
+    }
+    for f in unflags.chars() {
+        core.db.unset_flag(&name, f);
+        if f == 'x' {
+            env::remove_var(&name);
+        }
+    }
+
+    if ! name_only {
+        set_value(arg, core, layer, com)?;
+    }
+
+    for f in flags.chars().filter(|f| "nrx".contains(*f)) {
+        core.db.set_flag(&name, f);
+    }
+    Ok(())
+}
+
+fn declare_names(core: &mut ShellCore, names: &[String], layer: Option<usize>,
+                 flags: &str, unflags: &str, com: &str) -> bool {
+    let mut ans = true;
+    for name in names {
+        if let Err(e) = declare_name(name, core, layer, flags, unflags, com) {
+            error::print(&e, core);
+            ans = false;
+        }
+    }
+    ans
+}
+
+fn print_with_flags(core: &mut ShellCore, flags: &str) -> i32 {
+    for name in core.db.get_keys() {
+        let has_all = flags.chars().all(|f| match f {
+            'a' => core.db.is_array(&name),
+            'A' => core.db.is_assoc(&name),
+            f   => core.db.has_flag(&name, f),
+        });
+
+        if has_all {
+            core.db.print_declare(&name);
+        }
+    }
+    0
+}
+
+fn print_declare(core: &mut ShellCore, names: &[String], com: &str) -> i32 {
+    let mut exit_status = 0;
+    for name in names {
+        if ! core.db.print_declare(name) {
+            let msg = format!("{}: {}: not found", com, name);
+            error::print(&msg, core);
+            exit_status = 1;
+        }
+    }
+    exit_status
+}
+
+fn print_functions(core: &mut ShellCore, names: &[String], only_name: bool) -> i32 {
+    let mut names = names.to_vec();
+    if names.is_empty() {
+        names = core.db.functions.keys().cloned().collect();
+        names.sort();
+    }
+
+    let mut exit_status = 0;
+    for name in names {
+        match (core.db.functions.get(&name), only_name) {
+            (Some(_), true) => println!("declare -f {}", &name),
+            (Some(f), false) => println!("{}", &f.text),
+            (None, _) => exit_status = 1,
+        }
     }
+    exit_status
 }
 
 fn restore_and_return(core: &mut ShellCore, result: bool) -> i32 {
@@ -73,24 +178,17 @@ fn restore_and_return(core: &mut ShellCore, result: bool) -> i32 {
 
 pub fn local(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     let _ = core.db.pop_local();
-    let layer = if core.db.get_layer_num() > 1 {
-        core.db.get_layer_num() - 1 //The last element of data.parameters is for local itself.
-    }else{
-        eprintln!("sush: local: can only be used in a function");
-        return 1;
+    let layer = match core.db.function_layers.last() {
+        Some(n) => *n,
+        None => {
+            eprintln!("sush: local: can only be used in a function");
+            core.db.push_local();
+            return 1;
+        },
     };
 
-    if args.len() >= 3 && args[1] == "-a" {
-        let res = args[2..].iter().all(|a| set_local_array(a, core, layer).is_ok());
-        return restore_and_return(core, res);
-    }
-
-    if args.len() >= 3 && args[1] == "-A" {
-        let res = args[2..].iter().all(|a| core.db.set_assoc(a, Some(layer)).is_ok());
-        return restore_and_return(core, res);
-    }
-
-    let res = args[1..].iter().all(|a| set_local(a, core, layer).is_ok());
+    let (flags, unflags, names) = split_options(&args[1..]);
+    let res = declare_names(core, &names, Some(layer), &flags, &unflags, "local");
     restore_and_return(core, res)
 }
 
@@ -99,37 +197,57 @@ pub fn declare(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
         return print_all(core);
     }
 
-    let mut args = arg::dissolve_options(args);
+    let (flags, unflags, names) = split_options(&args[1..]);
+    if let Some(c) = (flags.clone() + &unflags).chars().find(|c| ! "aAfFgilnprux".contains(*c)) {
+        let msg = format!("{}: -{}: invalid option", &args[0], c);
+        error::print(&msg, core);
+        eprintln!("{}: usage: {} [-aAfFgilnprux] [-p] [name[=value] ...]", &args[0], &args[0]);
+        return 2;
+    }
 
-    let name = args.pop().unwrap();
-    if args.contains(&"-r".to_string()) {
-        core.db.set_flag(&name, 'r');
-        return 0;
+    if flags.contains('f') || flags.contains('F') {
+        return print_functions(core, &names, flags.contains('F'));
     }
 
-    if args.contains(&"-a".to_string()) {
-        if ! utils::is_name(&name, core) {
-            return 1; //TODO: error message
-        }
-        if let Err(e) = core.db.set_array(&name, vec![], None) {
-            error::print(&e, core);
-            return 1;
-        }
+    if names.is_empty() {
+        let flags = flags.replace("p", "").replace("g", "");
+        return print_with_flags(core, &flags);
+    }
 
-        return 0;
+    if flags.contains('p') {
+        return print_declare(core, &names, &args[0]);
     }
 
-    if args.contains(&"-A".to_string()) {
-        if ! utils::is_name(&name, core) {
-            return 1; //TODO: error message
-        }
-        if let Err(e) = core.db.set_assoc(&name, None) {
-            error::print(&e, core);
-            return 1;
-        }
+    core.db.pop_local();
+    let layer = match flags.contains('g') {
+        true  => Some(0),
+        false => core.db.function_layers.last().copied(),
+    };
 
-        return 0;
+    let res = declare_names(core, &names, layer, &flags, &unflags, &args[0]);
+    restore_and_return(core, res)
+}
+
+pub fn export(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (flags, _, names) = split_options(&args[1..]);
+    if names.is_empty() || flags.contains('p') {
+        return print_with_flags(core, "x");
     }
 
-    0
+    let res = match flags.contains('n') {
+        true  => declare_names(core, &names, None, "", "x", "export"),
+        false => declare_names(core, &names, None, "x", "", "export"),
+    };
+    if res {0} else {1}
+}
+
+pub fn readonly(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (flags, _, names) = split_options(&args[1..]);
+    if names.is_empty() || flags.contains('p') {
+        return print_with_flags(core, "r");
+    }
+
+    let flags = flags.chars().filter(|c| "aA".contains(*c)).collect::<String>() + "r";
+    let res = declare_names(core, &names, None, &flags, "", "readonly");
+    if res {0} else {1}
 }
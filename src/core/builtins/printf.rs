@@ -1,125 +1,540 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::ShellCore;
-use crate::utils::error;
-use sprintf::PrintfError;
+use crate::{utils, ShellCore};
+use crate::utils::{ansi_c, error};
+use crate::elements::substitution::Substitution;
+use crate::utils::ansi_c::{Mode, Quote};
+use nix::libc;
+use std::ffi::CString;
+use std::iter::Peekable;
+use std::str::Chars;
 
-fn split_format(format: &str) -> (Vec<String>, Option<String>) {
-    let mut escaped = false;
-    let mut percent = false;
-    let mut len = 0;
-    let mut len_prev = 0;
-    let mut ans = vec![];
+#[derive(Debug, Default)]
+struct Spec {
+    flags: String,
+    width: Option<usize>,
+    precision: Option<usize>,
+    time_format: String,
+    conv: char,
+}
+
+impl Spec {
+    fn has(&self, flag: char) -> bool {
+        self.flags.contains(flag)
+    }
 
-    for c in format.chars() {
-        if c == '\\' || escaped {
-            len += c.len_utf8();
-            escaped = ! escaped;
-            percent = false;
-            continue;
+    fn pad(&self, prefix: &str, body: &str, zero_fill: bool) -> String {
+        let len = prefix.chars().count() + body.chars().count();
+        let fill = self.width.unwrap_or(0).saturating_sub(len);
+
+        if self.has('-') {
+            format!("{}{}{}", prefix, body, " ".repeat(fill))
+        }else if zero_fill && self.has('0') {
+            format!("{}{}{}", prefix, "0".repeat(fill), body)
+        }else{
+            format!("{}{}{}", " ".repeat(fill), prefix, body)
         }
+    }
 
-        len += c.len_utf8();
-        if c == '%' {
-            percent = true;
-            continue;
+    fn pad_str(&self, s: &str) -> String {
+        let s = match self.precision {
+            Some(p) => s.chars().take(p).collect(),
+            None    => s.to_string(),
+        };
+        self.pad("", &s, false)
+    }
+
+    fn sign(&self, negative: bool) -> &str {
+        match (negative, self.has('+'), self.has(' ')) {
+            (true, _, _) => "-",
+            (_, true, _) => "+",
+            (_, _, true) => " ",
+            _ => "",
         }
+    }
 
-        if percent {
-            ans.push(format[len_prev..len].to_string());
-            len_prev = len;
-            percent = false;
+    fn format_int(&self, n: i64) -> String {
+        let (prefix, digits) = match self.conv {
+            'd' | 'i' => (self.sign(n < 0), n.unsigned_abs().to_string()),
+            'u' => ("", (n as u64).to_string()),
+            'o' => ("", format!("{:o}", n as u64)),
+            'x' => (if self.has('#') && n != 0 {"0x"} else {""}, format!("{:x}", n as u64)),
+            _   => (if self.has('#') && n != 0 {"0X"} else {""}, format!("{:X}", n as u64)),
+        };
+
+        let mut digits = match self.precision {
+            Some(0) if n == 0 => String::new(),
+            Some(p) if p > digits.len() => "0".repeat(p - digits.len()) + &digits,
+            _ => digits,
+        };
+        if self.conv == 'o' && self.has('#') && ! digits.starts_with('0') {
+            digits.insert(0, '0');
         }
+
+        self.pad(prefix, &digits, self.precision.is_none())
+    }
+
+    fn format_float(&self, f: f64) -> String {
+        let upper = self.conv.is_ascii_uppercase();
+        let body = if f.is_nan() {
+            "nan".to_string()
+        }else if f.is_infinite() {
+            "inf".to_string()
+        }else{
+            let prec = self.precision.unwrap_or(6);
+            match self.conv.to_ascii_lowercase() {
+                'f' => format!("{:.*}", prec, f.abs()),
+                'e' => exp_notation(f.abs(), prec),
+                'g' => general_notation(f.abs(), prec, self.has('#')),
+                _   => hex_notation(f.abs(), self.precision),
+            }
+        };
+
+        let body = match upper {
+            true  => body.to_uppercase(),
+            false => body,
+        };
+        let prefix = self.sign(f.is_sign_negative());
+        self.pad(prefix, &body, f.is_finite())
     }
+}
+
+fn exp_notation(f: f64, prec: usize) -> String {
+    let s = format!("{:.*e}", prec, f);
+    let (mantissa, exp) = s.split_once('e').unwrap();
+    let exp = exp.parse::<i32>().unwrap_or(0);
+    format!("{}e{}{:02}", mantissa, if exp < 0 {'-'} else {'+'}, exp.abs())
+}
 
-    match format[len_prev..len].is_empty() {
-        true  => (ans, None),
-        false => (ans, Some(format[len_prev..len].to_string()) ),
+fn general_notation(f: f64, prec: usize, alt: bool) -> String {
+    let prec = prec.max(1);
+    let e = format!("{:.*e}", prec - 1, f);
+    let exp = e.split_once('e').unwrap().1.parse::<i32>().unwrap_or(0);
+
+    let s = match exp < -4 || exp >= prec as i32 {
+        true  => exp_notation(f, prec - 1),
+        false => format!("{:.*}", (prec as i32 - 1 - exp) as usize, f),
+    };
+    if alt || ! s.contains('.') {
+        return s;
     }
+
+    let (mantissa, exp) = match s.split_once('e') {
+        Some((m, e)) => (m.to_string(), format!("e{}", e)),
+        None => (s, String::new()),
+    };
+    mantissa.trim_end_matches('0').trim_end_matches('.').to_string() + &exp
 }
 
-fn output(pattern: &str, args: &mut Vec<String>) -> Result<String, PrintfError> {
-    let mut ans = String::new();
-    let (parts, tail) = split_format(&pattern);
+fn hex_notation(f: f64, precision: Option<usize>) -> String {
+    if f == 0.0 {
+        return match precision {
+            Some(p) if p > 0 => format!("0x0.{}p+0", "0".repeat(p)),
+            _ => "0x0p+0".to_string(),
+        };
+    }
 
-    while parts.len() > args.len() {
-        args.push(String::new());
+    let bits = f.to_bits();
+    let mut exp = ((bits >> 52) & 0x7ff) as i64;
+    let mut frac = bits & 0xfffffffffffff;
+    if exp == 0 {
+        exp = 1;
+        while frac & (1 << 52) == 0 {
+            frac <<= 1;
+            exp -= 1;
+        }
+    }else{
+        frac |= 1 << 52;
     }
 
-    for i in 0..parts.len() {
-        if parts[i].contains("%d") {
-            if let Ok(_) = args[i].parse::<i32>() {
-                ans += &parts[i].replace("%d", &args[i]);
-            }
-        }else {
-            ans += &sprintf::sprintf!(&parts[i], args[i])?;
+    /* the leading hex digit holds four bits as glibc does for long double */
+    let mut sig = (frac as u128) << 11;
+    let mut exp = exp - 1023 - 3;
+    let mut digits = 15;
+    if let Some(p) = precision.filter(|p| *p < 15) {
+        let shift = (15 - p) * 4;
+        let half = 1u128 << (shift - 1);
+        sig = (sig + half) >> shift << shift;
+        if sig >> 64 != 0 {
+            sig >>= 4;
+            exp += 4;
         }
+        digits = p;
     }
 
-    if let Some(s) = tail {
-        ans += &s;
+    let hex = format!("{:016x}", sig as u64);
+    let mut tail = hex[1..1+digits].to_string();
+    if precision.is_none() {
+        tail = tail.trim_end_matches('0').to_string();
+    }else if let Some(p) = precision {
+        tail += &"0".repeat(p.saturating_sub(15));
     }
-    Ok(ans)
+
+    let point = if tail.is_empty() {""} else {"."};
+    format!("0x{}{}{}p{}{}", &hex[..1], point, tail, if exp < 0 {'-'} else {'+'}, exp.abs())
 }
 
-pub fn printf(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    if args.len() < 2 || args[1] == "--help"
-    || args[1] == "-v" && args.len() == 3 {
-        let msg = format!("printf: usage: printf [-v var] format [arguments]");
-        error::print(&msg, core);
-        return 2;
+pub fn strftime(format: &str, time: i64) -> String {
+    let format = match format {
+        "" => "%X",
+        f  => f,
+    };
+    let cformat = match CString::new(format) {
+        Ok(f) => f,
+        Err(_) => return String::new(),
+    };
+
+    let t = time as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    let mut buf = vec![0u8; 1024];
+    let len = unsafe {
+        libc::localtime_r(&t, &mut tm);
+        libc::strftime(buf.as_mut_ptr() as *mut libc::c_char, buf.len(), cformat.as_ptr(), &tm)
+    };
+    String::from_utf8_lossy(&buf[..len]).to_string()
+}
+
+struct Printf<'a> {
+    args: &'a [String],
+    pos: usize,
+    out: Vec<u8>,
+    errors: Vec<(usize, String)>,
+    start_time: i64,
+}
+
+impl Printf<'_> {
+    fn next_arg(&mut self) -> Option<String> {
+        let ans = self.args.get(self.pos).cloned();
+        self.pos += 1;
+        ans
     }
 
-    if args[1] == "-v" && args.len() == 2 {
-        let msg = format!("printf: -v: option requires an argument");
-        error::print(&msg, core);
-        let msg = format!("printf: usage: printf [-v var] format [arguments]");
-        error::print(&msg, core);
-        return 2;
+    fn error(&mut self, msg: String) {
+        self.errors.push((self.out.len(), msg));
     }
 
-    if args[1] == "-v" {
-        let s = match output(&args[3], &mut args[4..].to_vec()) {
-            Ok(ans) => ans,
-            Err(e) => {
-                let msg = format!("printf: {}", e);
-                error::print(&msg, core);
-                return 1;
+    fn invalid_number(&mut self, arg: &str) {
+        self.error(format!("printf: {}: invalid number", arg));
+    }
+
+    fn parse_int(&mut self, arg: &str) -> i64 {
+        let s = arg.trim_start();
+        if s.is_empty() {
+            if ! arg.is_empty() {
+                self.invalid_number(arg);
+            }
+            return 0;
+        }
+
+        if s.starts_with('\'') || s.starts_with('"') {
+            return s.chars().nth(1).map(|c| c as i64).unwrap_or(0);
+        }
+
+        let (negative, body) = match s.chars().next() {
+            Some('-') => (true, &s[1..]),
+            Some('+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+        let (radix, digits) = if body.starts_with("0x") || body.starts_with("0X") {
+            (16, &body[2..])
+        }else if body.starts_with('0') && body.len() > 1 {
+            (8, &body[1..])
+        }else{
+            (10, body)
+        };
+
+        let len = digits.chars().take_while(|c| c.is_digit(radix)).count();
+        let mut overflow = false;
+        let mut n: i128 = 0;
+        for c in digits[..len].chars() {
+            n = n * radix as i128 + c.to_digit(radix).unwrap() as i128;
+            if n > u64::MAX as i128 {
+                overflow = true;
+                n = u64::MAX as i128;
+            }
+        }
+        if negative {
+            n = -n;
+        }
+
+        if len < digits.len() || (len == 0 && radix == 10) {
+            self.invalid_number(arg);
+        }else if overflow || n > i64::MAX as i128 || n < i64::MIN as i128 {
+            let msg = format!("printf: warning: {}: Numerical result out of range", arg);
+            self.error(msg);
+            n = n.clamp(i64::MIN as i128, i64::MAX as i128);
+        }
+        n as i64
+    }
+
+    fn parse_float(&mut self, arg: &str) -> f64 {
+        let s = arg.trim_start();
+        if s.starts_with('\'') || s.starts_with('"') || s.contains("0x") || s.contains("0X") {
+            return self.parse_int(arg) as f64;
+        }
+        if s.is_empty() {
+            return self.parse_int(arg) as f64;
+        }
+        if let Ok(f) = s.parse::<f64>() {
+            return f;
+        }
+
+        self.invalid_number(arg);
+        let mut end = s.len();
+        while end > 0 {
+            if let Ok(f) = s[..end].parse::<f64>() {
+                return f;
+            }
+            end -= 1;
+            while ! s.is_char_boundary(end) {
+                end -= 1;
+            }
+        }
+        0.0
+    }
+
+    fn star_arg(&mut self) -> i64 {
+        match self.next_arg() {
+            Some(a) => self.parse_int(&a),
+            None => 0,
+        }
+    }
+
+    fn parse_spec(&mut self, chars: &mut Peekable<Chars>) -> Result<Spec, String> {
+        let mut spec = Spec::default();
+        let mut text = "%".to_string();
+
+        while let Some(c) = chars.next_if(|c| "-+ #0'".contains(*c)) {
+            spec.flags.push(c);
+            text.push(c);
+        }
+
+        if chars.next_if_eq(&'*').is_some() {
+            text.push('*');
+            let w = self.star_arg();
+            if w < 0 {
+                spec.flags.push('-');
+            }
+            spec.width = Some(w.unsigned_abs() as usize);
+        }else{
+            let mut w = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+                w.push(c);
+            }
+            text += &w;
+            spec.width = w.parse::<usize>().ok();
+        }
+
+        if chars.next_if_eq(&'.').is_some() {
+            text.push('.');
+            if chars.next_if_eq(&'*').is_some() {
+                text.push('*');
+                let p = self.star_arg();
+                spec.precision = if p < 0 {None} else {Some(p as usize)};
+            }else{
+                let mut p = String::new();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+                    p.push(c);
+                }
+                text += &p;
+                spec.precision = Some(p.parse::<usize>().unwrap_or(0));
+            }
+        }
+
+        while let Some(c) = chars.next_if(|c| "hlLjzt".contains(*c)) {
+            text.push(c);
+        }
+
+        if chars.next_if_eq(&'(').is_some() {
+            text.push('(');
+            for c in chars.by_ref() {
+                text.push(c);
+                if c == ')' {
+                    break;
+                }
+                spec.time_format.push(c);
+            }
+            if chars.peek() != Some(&'T') {
+                return Err(format!("printf: `{}': missing time format specification", text));
+            }
+        }
+
+        match chars.next() {
+            Some(c) if "diouxXeEfFgGaAcsbqQT".contains(c) => spec.conv = c,
+            Some(c) => return Err(format!("printf: `{}': invalid format character", c)),
+            None => return Err(format!("printf: `{}': missing format character", text)),
+        }
+        Ok(spec)
+    }
+
+    fn convert(&mut self, spec: &Spec) -> bool {
+        let arg = self.next_arg();
+        let s = match spec.conv {
+            'd' | 'i' | 'o' | 'u' | 'x' | 'X' => {
+                let n = match &arg {
+                    Some(a) => self.parse_int(a),
+                    None => 0,
+                };
+                spec.format_int(n)
+            },
+            'c' => spec.pad("", &arg.unwrap_or_default().chars().take(1).collect::<String>(), false),
+            's' => spec.pad_str(&arg.unwrap_or_default()),
+            'q' => spec.pad("", &ansi_c::quote(&arg.unwrap_or_default(), Quote::Backslash), false),
+            'Q' => {
+                let arg = arg.unwrap_or_default();
+                let arg = match spec.precision {
+                    Some(p) => arg.chars().take(p).collect(),
+                    None    => arg,
+                };
+                spec.pad("", &ansi_c::quote(&arg, Quote::Backslash), false)
+            },
+            'b' => {
+                let mut buf = vec![];
+                let complete = ansi_c::unescape_to(&arg.unwrap_or_default(), Mode::PrintfB, &mut buf);
+                self.out.extend(spec.pad_str(&String::from_utf8_lossy(&buf)).as_bytes());
+                return complete;
+            },
+            'T' => {
+                let t = match arg.as_deref() {
+                    None | Some("") => -1,
+                    Some(a) => self.parse_int(a),
+                };
+                let t = match t {
+                    -1 => utils::clock::get_epochseconds(&mut vec![]).parse().unwrap_or(0),
+                    -2 => self.start_time,
+                    t  => t,
+                };
+                spec.pad_str(&strftime(&spec.time_format, t))
+            },
+            _ => {
+                let f = match &arg {
+                    Some(a) => self.parse_float(a),
+                    None => 0.0,
+                };
+                spec.format_float(f)
             },
         };
 
-        if args[2].contains("[") {
-            let tokens = args[2].split('[').collect::<Vec<&str>>();
-            let name = tokens[0].to_string();
-            let subscript = tokens[1].split(']').nth(0).unwrap().to_string();
+        self.out.extend(s.as_bytes());
+        true
+    }
+
+    /* returns false when the output must be stopped */
+    fn format_once(&mut self, format: &str) -> bool {
+        let mut chars = format.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c == '\\' {
+                ansi_c::unescape_char(&mut chars, Mode::Printf, &mut self.out);
+                continue;
+            }
+            if c != '%' {
+                ansi_c::push_char(c, &mut self.out);
+                continue;
+            }
+            if chars.next_if_eq(&'%').is_some() {
+                self.out.push(b'%');
+                continue;
+            }
 
-            let result = match subscript.parse::<usize>() {
-                Ok(n) => core.db.set_array_elem(&name, &s, n, None),
-                _ => core.db.set_assoc_elem(&name, &subscript, &s, None),
+            let spec = match self.parse_spec(&mut chars) {
+                Ok(spec) => spec,
+                Err(e) => {
+                    self.error(e);
+                    return false;
+                },
             };
-            if let Err(e) = result {
-                let msg = format!("printf: {}", e);
-                error::print(&msg, core);
-                return 2;
+            if ! self.convert(&spec) {
+                return false;
             }
-            return 0;
         }
-        if ! core.db.set_param(&args[2], &s, None).is_ok() {
-            return 2;
+        true
+    }
+
+    fn format(&mut self, format: &str) {
+        loop {
+            let start = self.pos;
+            if ! self.format_once(format)
+            || self.pos == start || self.pos >= self.args.len() {
+                return;
+            }
+        }
+    }
+}
+
+fn usage(core: &mut ShellCore) -> i32 {
+    let msg = "printf: usage: printf [-v var] format [arguments]";
+    error::print(msg, core);
+    2
+}
+
+fn write_error(core: &mut ShellCore, e: &str) -> i32 {
+    let msg = format!("printf: write error: {}", e);
+    error::print(&msg, core);
+    1
+}
+
+pub fn printf(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut pos = 1;
+    let mut var = None;
+
+    if args.len() > 1 && args[1] == "--help" {
+        return usage(core);
+    }
+    if args.len() > 1 && args[1] == "-v" {
+        if args.len() == 2 {
+            error::print("printf: -v: option requires an argument", core);
+            return usage(core);
         }
+        var = Some(args[2].clone());
+        pos = 3;
+    }
+    if pos < args.len() && args[pos] == "--" {
+        pos += 1;
+    }
+    if pos >= args.len() {
+        return usage(core);
+    }
+
+    let start_time = match core.db.get_param("EPOCHSECONDS") {
+        Ok(now) => now.parse::<i64>().unwrap_or(0)
+                   - core.db.get_param("SECONDS").unwrap_or_default().parse::<i64>().unwrap_or(0),
+        Err(_) => 0,
+    };
+    let mut p = Printf { args: &args[pos+1..], pos: 0, out: vec![], errors: vec![], start_time };
+    p.format(&args[pos]);
 
-        return 0;
+    let mut status = 0;
+
+    let mut printed = 0;
+    for (n, e) in &p.errors {
+        if ! e.starts_with("printf: warning") {
+            status = 1;
+        }
+        if var.is_none() {
+            if let Err(e) = utils::file::write_stdout(&p.out[printed..*n]) {
+                return write_error(core, &e);
+            }
+            printed = *n;
+        }
+        error::print(e, core);
     }
 
-    let s = match output(&args[1], &mut args[2..].to_vec()) {
-        Ok(ans) => ans,
-        Err(e) => {
-            let msg = format!("printf: {}", e);
-            error::print(&msg, core);
-            return 1;
+    match var {
+        Some(name) => {
+            let value = String::from_utf8_lossy(&p.out).to_string();
+            if let Err(e) = Substitution::set_target(&name, &value, core) {
+                let msg = format!("printf: {}", e);
+                error::print(&msg, core);
+                return 2;
+            }
         },
-    };
-    print!("{}", &s);
-    0
+        None => {
+            if let Err(e) = utils::file::write_stdout(&p.out[printed..]) {
+                return write_error(core, &e);
+            }
+        },
+    }
+    status
 }
@@ -1,73 +1,351 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::ShellCore;
+use crate::{utils, ShellCore};
+use crate::elements::subword;
+use crate::feeder::terminal;
 use crate::utils::error;
+use nix::{fcntl, unistd};
+use nix::poll::{PollFd, PollFlags, PollTimeout};
+use nix::sys::termios;
+use nix::sys::termios::{LocalFlags, SetArg, SpecialCharacterIndices, Termios};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::os::fd::{BorrowedFd, RawFd};
+use std::time::{Duration, Instant};
 
-fn is_varname(s :&String) -> bool {
-    if s.is_empty() {
-        return false;
+#[derive(Debug)]
+struct Options {
+    raw: bool,
+    array: Option<String>,
+    delim: char,
+    nchars: Option<usize>,
+    exact: bool,
+    prompt: Option<String>,
+    silent: bool,
+    timeout: Option<f64>,
+    fd: RawFd,
+    editing: bool,
+    names: Vec<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            raw: false,
+            array: None,
+            delim: '\n',
+            nchars: None,
+            exact: false,
+            prompt: None,
+            silent: false,
+            timeout: None,
+            fd: 0,
+            editing: false,
+            names: vec![],
+        }
     }
+}
+
+struct Reader {
+    fd: RawFd,
+    deadline: Option<Instant>,
+    buffer: Option<VecDeque<char>>,
+    timed_out: bool,
+}
 
-    let first_ch = s.chars().nth(0).unwrap();
+impl Reader {
+    fn wait(&mut self) -> bool {
+        let deadline = match self.deadline {
+            Some(d) => d,
+            None => return true,
+        };
 
-    if '0' <= first_ch && first_ch <= '9' {
-        return false;
+        let rest = deadline.saturating_duration_since(Instant::now()).as_millis();
+        let timeout = PollTimeout::try_from(rest as i32).unwrap_or(PollTimeout::MAX);
+        let fd = unsafe { BorrowedFd::borrow_raw(self.fd) };
+        let mut fds = [PollFd::new(fd, PollFlags::POLLIN)];
+        match nix::poll::poll(&mut fds, timeout) {
+            Ok(n) if n > 0 => true,
+            _ => {
+                self.timed_out = true;
+                false
+            },
+        }
     }
 
-    let name_c = |c| ('a' <= c && c <= 'z') || ('A' <= c && c <= 'Z')
-                     || ('0' <= c && c <= '9') || '_' == c;
-    s.chars().position(|c| !name_c(c)) == None
-}
+    fn read_byte(&mut self) -> Option<u8> {
+        if ! self.wait() {
+            return None;
+        }
 
-pub fn read(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    if args.len() <= 1 {
-        return 0;
-    }
-
-    for a in &args[1..] {
-        if ! is_varname(&a) {
-            eprintln!("bash: read: `{}': not a valid identifier", &a);
-            return 1;
-        }else{
-            if let Err(e) = core.db.set_param(&a, "", None) {
-                //let msg = error::readonly(&a);
-                error::print(&e, core);
-                return 1;
-            }
+        let mut ch = [0; 1];
+        match unistd::read(self.fd, &mut ch) {
+            Ok(1) => Some(ch[0]),
+            _ => None,
         }
     }
 
-    let mut line = String::new();
-    let len = std::io::stdin()
-        .read_line(&mut line)
-        .expect("SUSHI INTERNAL ERROR: Failed to read line");
+    fn read_char(&mut self) -> Option<char> {
+        if let Some(buf) = self.buffer.as_mut() {
+            return buf.pop_front();
+        }
 
-    let mut pos = 1;
-    let mut overflow = String::new();
-    for w in line.trim_end().split(' ') {
-        if pos < args.len()-1 {
-            if let Err(e) = core.db.set_param(&args[pos], &w, None) {
-                //let msg = error::readonly(&args[pos]);
-                error::print(&e, core);
-                return 1;
+        let lead = self.read_byte()?;
+        let width = match lead {
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => 1,
+        };
+
+        let mut bytes = vec![lead];
+        for _ in 1..width {
+            match self.read_byte() {
+                Some(b) => bytes.push(b),
+                None => break,
             }
+        }
+
+        match String::from_utf8(bytes) {
+            Ok(s) => s.chars().next(),
+            Err(_) => Some('\u{FFFD}'),
+        }
+    }
+}
+
+fn parse_options(core: &mut ShellCore, args: &[String]) -> Result<Options, String> {
+    let mut opts = Options::default();
+    let mut pos = 1;
+
+    while pos < args.len() && args[pos].starts_with("-") && args[pos].len() > 1 {
+        if args[pos] == "--" {
             pos += 1;
-        }else{
-            if overflow.len() != 0 {
-                overflow += " ";
+            break;
+        }
+
+        let arg = args[pos].clone();
+        for (i, c) in arg[1..].char_indices() {
+            match c {
+                'r' => { opts.raw = true; continue; },
+                's' => { opts.silent = true; continue; },
+                'e' => { opts.editing = true; continue; },
+                'a' | 'd' | 'n' | 'N' | 'p' | 't' | 'u' => {},
+                _ => return Err(format!("read: -{}: invalid option", c)),
             }
-            overflow += &w;
-            if let Err(e) = core.db.set_param(&args[pos], &overflow, None) {
-                //let msg = error::readonly(&args[pos]);
-                error::print(&e, core);
-                return 1;
+
+            let value = match &arg[i+2..] {
+                "" => {
+                    pos += 1;
+                    match args.get(pos) {
+                        Some(v) => v.clone(),
+                        None => return Err(format!("read: -{}: option requires an argument", c)),
+                    }
+                },
+                rest => rest.to_string(),
+            };
+
+            match c {
+                'a' => opts.array = Some(value),
+                'd' => opts.delim = value.chars().next().unwrap_or('\0'),
+                'n' | 'N' => {
+                    let n = value.parse::<usize>()
+                                 .map_err(|_| format!("read: {}: invalid number", value))?;
+                    opts.nchars = Some(n);
+                    opts.exact = c == 'N';
+                },
+                'p' => opts.prompt = Some(value),
+                't' => match value.parse::<f64>() {
+                    Ok(t) if t >= 0.0 => opts.timeout = Some(t),
+                    _ => return Err(format!("read: {}: invalid timeout specification", value)),
+                },
+                _ => {
+                    opts.fd = value.parse::<RawFd>()
+                        .map_err(|_| format!("read: {}: invalid file descriptor specification", value))?;
+                },
             }
+            break;
         }
+        pos += 1;
+    }
+
+    for name in args[pos..].iter().chain(opts.array.iter()) {
+        if ! utils::is_param(name) || name.parse::<usize>().is_ok() {
+            let msg = format!("read: `{}': not a valid identifier", name);
+            error::print(&msg, core);
+            return Err(String::new());
+        }
+    }
+
+    opts.names = args[pos..].to_vec();
+    Ok(opts)
+}
+
+fn set_terminal(opts: &Options) -> Option<Termios> {
+    if ! unistd::isatty(opts.fd).unwrap_or(false) || opts.editing {
+        return None;
+    }
+    if ! opts.silent && opts.nchars.is_none() {
+        return None;
+    }
+
+    let fd = unsafe { BorrowedFd::borrow_raw(opts.fd) };
+    let backup = termios::tcgetattr(fd).ok()?;
+    let mut term = backup.clone();
+    if opts.silent {
+        term.local_flags.remove(LocalFlags::ECHO);
+    }
+    if opts.nchars.is_some() {
+        term.local_flags.remove(LocalFlags::ICANON);
+        term.control_chars[SpecialCharacterIndices::VMIN as usize] = 1;
+        term.control_chars[SpecialCharacterIndices::VTIME as usize] = 0;
+    }
+    let _ = termios::tcsetattr(fd, SetArg::TCSANOW, &term);
+    Some(backup)
+}
+
+fn restore_terminal(opts: &Options, backup: Option<Termios>) {
+    if let Some(term) = backup {
+        let fd = unsafe { BorrowedFd::borrow_raw(opts.fd) };
+        let _ = termios::tcsetattr(fd, SetArg::TCSANOW, &term);
+    }
+}
+
+fn read_line(reader: &mut Reader, opts: &Options) -> (Vec<(char, bool)>, bool) {
+    let mut line = vec![];
+
+    loop {
+        if let Some(n) = opts.nchars {
+            if line.len() >= n {
+                return (line, false);
+            }
+        }
+
+        let c = match reader.read_char() {
+            Some(c) => c,
+            None => return (line, true),
+        };
+
+        if c == opts.delim && ! opts.exact {
+            return (line, false);
+        }
+
+        if c == '\\' && ! opts.raw {
+            match reader.read_char() {
+                Some('\n') => {},
+                Some(c) => line.push((c, true)),
+                None => return (line, true),
+            }
+            continue;
+        }
+
+        line.push((c, false));
+    }
+}
+
+fn to_string(chars: &[(char, bool)]) -> String {
+    chars.iter().map(|c| c.0).collect()
+}
+
+fn split(line: &[(char, bool)], ifs: &str, num: Option<usize>) -> Vec<String> {
+    subword::ifs_split(line, ifs, num).into_iter()
+        .filter(|(f, keep)| *keep || ! f.is_empty())
+        .map(|(f, _)| f).collect()
+}
+
+fn set_variables(core: &mut ShellCore, opts: &Options, line: &[(char, bool)]) -> Result<(), String> {
+    let ifs = core.db.get_ifs();
+
+    if let Some(name) = &opts.array {
+        let fields = match opts.exact {
+            true  => vec![to_string(line)],
+            false => split(line, &ifs, None),
+        };
+        return core.db.set_array(name, fields, None);
+    }
+
+    if opts.names.is_empty() {
+        return core.db.set_param("REPLY", &to_string(line), None);
+    }
+
+    let mut fields = match opts.exact {
+        true  => vec![to_string(line)],
+        false => split(line, &ifs, Some(opts.names.len())),
+    };
+    fields.resize(opts.names.len(), String::new());
+
+    for (name, value) in opts.names.iter().zip(fields.iter()) {
+        core.db.set_param(name, value, None)?;
+    }
+    Ok(())
+}
+
+fn make_reader(core: &mut ShellCore, opts: &Options) -> Reader {
+    let mut reader = Reader {
+        fd: opts.fd,
+        deadline: opts.timeout.map(|t| Instant::now() + Duration::from_secs_f64(t)),
+        buffer: None,
+        timed_out: false,
+    };
+
+    let tty = unistd::isatty(opts.fd).unwrap_or(false);
+    if opts.editing && opts.fd == 0 && tty {
+        let prompt = opts.prompt.clone().unwrap_or_default();
+        let line = terminal::edit_line(core, &prompt, "").unwrap_or_default();
+        reader.buffer = Some(line.chars().collect());
+    }else if let (Some(prompt), true) = (&opts.prompt, tty) {
+        eprint!("{}", prompt);
+        let _ = std::io::stderr().flush();
+    }
+
+    reader
+}
+
+pub fn read(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let opts = match parse_options(core, args) {
+        Ok(opts) => opts,
+        Err(e) if e.is_empty() => return 1,
+        Err(e) => {
+            error::print(&e, core);
+            return 2;
+        },
+    };
+
+    if fcntl::fcntl(opts.fd, fcntl::F_GETFD).is_err() {
+        let msg = format!("read: {}: invalid file descriptor: Bad file descriptor", opts.fd);
+        error::print(&msg, core);
+        return 1;
+    }
+
+    if opts.timeout == Some(0.0) {
+        let mut reader = Reader { fd: opts.fd, deadline: Some(Instant::now()),
+                                  buffer: None, timed_out: false };
+        return match reader.wait() {
+            true  => 0,
+            false => 1,
+        };
+    }
+
+    let backup = set_terminal(&opts);
+    let mut reader = make_reader(core, &opts);
+    let (line, eof) = read_line(&mut reader, &opts);
+    restore_terminal(&opts, backup);
+
+    if opts.silent && unistd::isatty(opts.fd).unwrap_or(false) && ! opts.editing {
+        eprintln!();
+    }
+
+    if let Err(e) = set_variables(core, &opts, &line) {
+        let msg = format!("read: {}", e);
+        error::print(&msg, core);
+        return 1;
     }
 
-    match len == 0 {
-        true  => 1,
-        false => 0,
+    if reader.timed_out {
+        128 + 14
+    }else if eof {
+        1
+    }else{
+        0
     }
 }
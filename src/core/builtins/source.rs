@@ -52,6 +52,7 @@ pub fn source(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     }
 
     io::replace(backup, 0);
+    core.run_trap("RETURN");
     core.source_function_level -= 1;
     core.source_level -= 1;
     core.return_flag = false;
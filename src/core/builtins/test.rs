@@ -0,0 +1,159 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::ShellCore;
+use crate::elements::expr::conditional::ConditionalExpr;
+use crate::utils::{error, file_check};
+
+fn is_unary_op(s: &str) -> bool {
+    match s.strip_prefix("-") {
+        Some(c) if c.len() == 1 => "abcdefghknoprstuvwxzGLNOS".contains(c),
+        _ => false,
+    }
+}
+
+fn is_binary_op(s: &str) -> bool {
+    ["=", "==", "!=", "<", ">", "-eq", "-ne", "-lt", "-le", "-gt", "-ge",
+     "-ef", "-nt", "-ot", "-a", "-o"].contains(&s)
+}
+
+fn to_int(s: &str) -> Result<i64, String> {
+    s.trim().parse::<i64>()
+     .map_err(|_| format!("{}: integer expression expected", s))
+}
+
+fn unary(op: &str, operand: &str, core: &mut ShellCore) -> Result<bool, String> {
+    ConditionalExpr::unary_eval(op, operand, core)
+}
+
+fn binary(left: &str, op: &str, right: &str) -> Result<bool, String> {
+    match op {
+        "=" | "==" => Ok(left == right),
+        "!="       => Ok(left != right),
+        "<"        => Ok(left < right),
+        ">"        => Ok(left > right),
+        "-a"       => Ok(! left.is_empty() && ! right.is_empty()),
+        "-o"       => Ok(! left.is_empty() || ! right.is_empty()),
+        "-ef" | "-nt" | "-ot" => Ok(file_check::metadata_comp(left, right, op)),
+        _ => Ok(ConditionalExpr::int_comparison(op, to_int(left)?, to_int(right)?)),
+    }
+}
+
+fn eval_term(args: &[String], pos: &mut usize, core: &mut ShellCore) -> Result<bool, String> {
+    if *pos >= args.len() {
+        return Err("argument expected".to_string());
+    }
+
+    if args[*pos] == "!" {
+        *pos += 1;
+        return Ok(! eval_term(args, pos, core)?);
+    }
+
+    if args[*pos] == "(" {
+        *pos += 1;
+        let ans = eval_or(args, pos, core)?;
+        if *pos >= args.len() || args[*pos] != ")" {
+            return Err("`)' expected".to_string());
+        }
+        *pos += 1;
+        return Ok(ans);
+    }
+
+    if *pos + 2 < args.len() && is_binary_op(&args[*pos+1])
+    && args[*pos+1] != "-a" && args[*pos+1] != "-o" {
+        *pos += 3;
+        return binary(&args[*pos-3], &args[*pos-2], &args[*pos-1]);
+    }
+
+    if *pos + 1 < args.len() && is_unary_op(&args[*pos]) {
+        *pos += 2;
+        return unary(&args[*pos-2], &args[*pos-1], core);
+    }
+
+    *pos += 1;
+    Ok(! args[*pos-1].is_empty())
+}
+
+fn eval_and(args: &[String], pos: &mut usize, core: &mut ShellCore) -> Result<bool, String> {
+    let mut ans = eval_term(args, pos, core)?;
+    while *pos < args.len() && args[*pos] == "-a" {
+        *pos += 1;
+        ans = eval_term(args, pos, core)? && ans;
+    }
+    Ok(ans)
+}
+
+fn eval_or(args: &[String], pos: &mut usize, core: &mut ShellCore) -> Result<bool, String> {
+    let mut ans = eval_and(args, pos, core)?;
+    while *pos < args.len() && args[*pos] == "-o" {
+        *pos += 1;
+        ans = eval_and(args, pos, core)? || ans;
+    }
+    Ok(ans)
+}
+
+fn eval_expr(args: &[String], core: &mut ShellCore) -> Result<bool, String> {
+    let mut pos = 0;
+    let ans = eval_or(args, &mut pos, core)?;
+    match pos < args.len() {
+        true  => Err(format!("{}: unexpected argument", &args[pos])),
+        false => Ok(ans),
+    }
+}
+
+fn eval(args: &[String], core: &mut ShellCore) -> Result<bool, String> {
+    match args.len() {
+        0 => Ok(false),
+        1 => Ok(! args[0].is_empty()),
+        2 => {
+            if args[0] == "!" {
+                Ok(args[1].is_empty())
+            }else if is_unary_op(&args[0]) {
+                unary(&args[0], &args[1], core)
+            }else{
+                Err(format!("{}: unary operator expected", &args[0]))
+            }
+        },
+        3 => {
+            if is_binary_op(&args[1]) {
+                binary(&args[0], &args[1], &args[2])
+            }else if args[0] == "!" {
+                Ok(! eval(&args[1..], core)?)
+            }else if args[0] == "(" && args[2] == ")" {
+                eval(&args[1..2], core)
+            }else{
+                Err(format!("{}: binary operator expected", &args[1]))
+            }
+        },
+        4 => {
+            if args[0] == "!" {
+                Ok(! eval(&args[1..], core)?)
+            }else if args[0] == "(" && args[3] == ")" {
+                eval(&args[1..3], core)
+            }else{
+                eval_expr(args, core)
+            }
+        },
+        _ => eval_expr(args, core),
+    }
+}
+
+pub fn test(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    if args[0] == "[" {
+        if args.last().unwrap() != "]" {
+            error::print("[: missing `]'", core);
+            return 2;
+        }
+        args.pop();
+    }
+
+    match eval(&args[1..], core) {
+        Ok(true)  => 0,
+        Ok(false) => 1,
+        Err(e) => {
+            let msg = format!("{}: {}", &args[0], e);
+            error::print(&msg, core);
+            2
+        },
+    }
+}
@@ -0,0 +1,93 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{signal, ShellCore};
+use crate::utils::error;
+use nix::sys::signal::Signal;
+
+fn to_trap_name(s: &str) -> Option<String> {
+    match s.to_uppercase().as_str() {
+        "0" | "EXIT" => return Some("EXIT".to_string()),
+        n @ ("ERR" | "DEBUG" | "RETURN") => return Some(n.to_string()),
+        _ => {},
+    }
+
+    signal::to_signal(s).map(|sig| sig.as_str().to_string())
+}
+
+fn print_trap(core: &ShellCore, name: &str) {
+    if let Some(command) = core.traps.get(name) {
+        println!("trap -- '{}' {}", command.replace("'", "'\\''"), name);
+    }
+}
+
+fn print_traps(core: &mut ShellCore, names: &[String]) -> i32 {
+    if names.is_empty() {
+        let mut keys = core.traps.keys().cloned().collect::<Vec<String>>();
+        keys.sort_by_key(|k| signal::to_signal(k).map(|s| s as i32).unwrap_or(0));
+        keys.iter().for_each(|k| print_trap(core, k));
+        return 0;
+    }
+
+    let mut ans = 0;
+    for n in names {
+        match to_trap_name(n) {
+            Some(name) => print_trap(core, &name),
+            None => ans = invalid_signal(core, n),
+        }
+    }
+    ans
+}
+
+pub fn print_signal_list() -> i32 {
+    for (i, sig) in Signal::iterator().enumerate() {
+        let end = if i%5 == 4 { "\n" }else{ "\t" };
+        print!("{:2}) {}{}", sig as i32, sig.as_str(), end);
+    }
+    if ! Signal::iterator().count().is_multiple_of(5) {
+        println!();
+    }
+    0
+}
+
+fn invalid_signal(core: &mut ShellCore, name: &str) -> i32 {
+    let msg = format!("trap: {}: invalid signal specification", name);
+    error::print(&msg, core);
+    1
+}
+
+pub fn trap(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut args = args[1..].to_vec();
+    if args.first().map(|a| a.as_str()) == Some("--") {
+        args.remove(0);
+    }
+
+    match args.first().map(|a| a.as_str()) {
+        None       => return print_traps(core, &[]),
+        Some("-p") => return print_traps(core, &args[1..]),
+        Some("-l") => return print_signal_list(),
+        _ => {},
+    }
+
+    let command = match args.len() == 1 {
+        true  => None,
+        false => Some(args.remove(0)).filter(|c| c != "-"),
+    };
+
+    let mut ans = 0;
+    for a in &args {
+        let name = match to_trap_name(a) {
+            Some(n) => n,
+            None    => {
+                ans = invalid_signal(core, a);
+                continue;
+            },
+        };
+
+        match &command {
+            Some(c) => core.set_trap(&name, c),
+            None    => core.unset_trap(&name),
+        }
+    }
+    ans
+}
@@ -5,11 +5,13 @@ mod data;
 mod getter;
 mod setter;
 
-use crate::{env, exit};
+use crate::env;
 use crate::elements::command::function_def::FunctionDefinition;
 use std::collections::{HashMap, HashSet};
 use crate::utils;
 use crate::utils::error;
+use crate::utils::ansi_c;
+use crate::utils::ansi_c::Quote;
 use self::data::Data;
 use self::data::assoc::AssocData;
 use self::data::single::SingleData;
@@ -25,6 +27,8 @@ pub struct DataBase {
     pub functions: HashMap<String, FunctionDefinition>,
     pub exit_status: i32,
     pub last_arg: String,
+    pub last_bg_pid: String,
+    pub function_layers: Vec<usize>,
 }
 
 impl DataBase {
@@ -59,12 +63,18 @@ impl DataBase {
 
     pub fn get_param(&mut self, name: &str) -> Result<String, String> {
         Self::name_check(name)?;
+        let name = &self.solve_nameref(name);
 
         if let Some(val) = getter::special_param(self, name) {
             return Ok(val);
         }
 
-        if name == "@" || name == "*" {   // $@ should return an array in a double quoted
+        if name == "*" {
+            let sep = self.get_ifs_separator();
+            return Ok(self.get_position_params().join(&sep));
+        }
+
+        if name == "@" {   // $@ should return an array in a double quoted
             return getter::connected_position_params(self);  // subword. Therefore another 
         }                                                   //access method should be used there. 
 
@@ -83,6 +93,7 @@ impl DataBase {
 
         if let Ok(v) = env::var(name) {
             let _ = self.set_param(name, &v, Some(0));
+            self.set_flag(name, 'x');
             return Ok(v);
         }
 
@@ -91,10 +102,34 @@ impl DataBase {
 
     pub fn get_array_elem(&mut self, name: &str, pos: &str) -> Result<String, String> {
         Self::name_check(name)?;
+        let name = &self.solve_nameref(name);
+        if pos == "*" && (self.is_array(name) || self.is_assoc(name)) {
+            let sep = self.get_ifs_separator();
+            return Ok(self.get_array_all(name).join(&sep));
+        }
         getter::array_elem(self, name, pos)
     }
 
+    pub fn get_ifs(&mut self) -> String {
+        match self.has_value("IFS") {
+            true  => self.get_param("IFS").unwrap_or_default(),
+            false => " \t\n".to_string(),
+        }
+    }
+
+    pub fn get_ifs_separator(&mut self) -> String {
+        self.get_ifs().chars().next().map(|c| c.to_string()).unwrap_or_default()
+    }
+
     pub fn has_value(&mut self, name: &str) -> bool {
+        if let Ok(n) = name.parse::<usize>() {
+            return n < self.position_parameters.last().map(|a| a.len()).unwrap_or(0);
+        }
+        if getter::special_param(self, name).is_some() {
+            return true;
+        }
+
+        let name = &self.solve_nameref(name);
         let num = self.params.len();
         for layer in (0..num).rev()  {
             if let Some(_) = self.params[layer].get(name) {
@@ -104,7 +139,52 @@ impl DataBase {
         false
     }
 
+    /* an array referred without a subscript has a value only in its element 0 */
+    pub fn has_scalar_value(&mut self, name: &str) -> bool {
+        match self.is_array(name) || self.is_assoc(name) {
+            true  => self.has_array_value(name, "0"),
+            false => self.has_value(name),
+        }
+    }
+
+    pub fn has_array_value(&mut self, name: &str, index: &str) -> bool {
+        let name = &self.solve_nameref(name);
+        let mut d = match getter::clone(self, name) {
+            Some(d) => d,
+            None    => return false,
+        };
+        let indexes = d.get_all_indexes().unwrap_or(vec!["0".to_string()]);
+        if index == "@" || index == "*" {
+            return ! indexes.is_empty();
+        }
+
+        let mut index = index.to_string();
+        if let (false, Ok(n)) = (d.is_assoc(), index.parse::<i64>()) {
+            if n < 0 {
+                let max = indexes.iter().filter_map(|i| i.parse::<i64>().ok()).max();
+                index = (max.unwrap_or(-1) + 1 + n).to_string();
+            }
+        }
+        indexes.contains(&index)
+    }
+
+    /* a negative index counts back from the end of the array */
+    pub fn get_array_index(&mut self, name: &str, index: &str) -> Result<usize, String> {
+        let bad_subscript = || format!("{}[{}]: bad array subscript", name, index);
+        let n = index.parse::<i64>().map_err(|_| bad_subscript())?;
+        if n >= 0 {
+            return Ok(n as usize);
+        }
+
+        let max = self.get_indexes_all(name).iter().filter_map(|i| i.parse::<i64>().ok()).max();
+        match max.unwrap_or(-1) + 1 + n {
+            m if m >= 0 => Ok(m as usize),
+            _ => Err(bad_subscript()),
+        }
+    }
+
     pub fn len(&mut self, key: &str) -> usize {
+        let key = &self.solve_nameref(key);
         match getter::clone(self, key).as_mut() {
             Some(d) => d.len(),
             _ => 0,
@@ -117,6 +197,7 @@ impl DataBase {
             return self.position_parameters[layer].clone();
         }
 
+        let name = &self.solve_nameref(name);
         match getter::clone(self, name).as_mut() {
             Some(d) => {
                 match d.get_all_as_array() {
@@ -128,7 +209,21 @@ impl DataBase {
         }
     }
 
+    pub fn get_indexes_all(&mut self, name: &str) -> Vec<String> {
+        if name == "@" || name == "*" {
+            let num = self.get_position_params().len();
+            return (1..=num).map(|i| i.to_string()).collect();
+        }
+
+        let name = &self.solve_nameref(name);
+        match getter::clone(self, name).as_mut() {
+            Some(d) => d.get_all_indexes().unwrap_or_default(),
+            None => vec![],
+        }
+    }
+
     pub fn is_array(&mut self, name: &str) -> bool {
+        let name = &self.solve_nameref(name);
         match getter::clone(self, name).as_mut() {
             Some(d) => return d.is_array(),
             _ => false,
@@ -136,6 +231,7 @@ impl DataBase {
     }
 
     pub fn is_assoc(&mut self, name: &str) -> bool {
+        let name = &self.solve_nameref(name);
         match getter::clone(self, name) {
             Some(d) => d.is_assoc(),
             None => false,
@@ -149,11 +245,40 @@ impl DataBase {
         }
     }
 
-    fn has_flag(&mut self, name: &str, flag: char) -> bool {
-        let layer = self.param_options.len() - 1;
-        match self.param_options[layer].get(name) {
-            None => false,
-            Some(e) => e.contains(flag),
+    pub fn get_flags(&mut self, name: &str) -> String {
+        let flags = match self.get_layer_pos(name) {
+            Some(layer) => self.param_options[layer].get(name),
+            None => self.param_options.iter().rev().find_map(|opts| opts.get(name)),
+        };
+        flags.cloned().unwrap_or_default()
+    }
+
+    pub fn has_flag(&mut self, name: &str, flag: char) -> bool {
+        self.get_flags(name).contains(flag)
+    }
+
+    fn solve_nameref(&mut self, name: &str) -> String {
+        let mut name = name.to_string();
+        for _ in 0..10 {
+            if ! self.has_flag(&name, 'n') {
+                break;
+            }
+            match getter::clone(self, &name).map(|mut d| d.get_as_single()) {
+                Some(Ok(next)) if utils::is_param(&next) => name = next,
+                _ => break,
+            }
+        }
+        name
+    }
+
+    fn apply_case_flags(&mut self, name: &str, val: &str) -> String {
+        let flags = self.get_flags(name);
+        if flags.contains('l') {
+            val.to_lowercase()
+        }else if flags.contains('u') {
+            val.to_uppercase()
+        }else{
+            val.to_string()
         }
     }
 
@@ -180,20 +305,26 @@ impl DataBase {
 
     pub fn set_param(&mut self, name: &str, val: &str, layer: Option<usize>) -> Result<(), String> {
         Self::name_check(name)?;
+        let name = &self.solve_nameref(name);
+        let val = &self.apply_case_flags(name, val);
         self.write_check(name)?;
         let layer = self.get_target_layer(name, layer);
         SingleData::set_value(&mut self.params[layer], name, val)
     }
 
-    pub fn set_array_elem(&mut self, name: &str, val: &String, pos: usize, layer: Option<usize>) -> Result<(), String> {
+    pub fn set_array_elem(&mut self, name: &str, val: &str, pos: usize, layer: Option<usize>) -> Result<(), String> {
         Self::name_check(name)?;
+        let name = &self.solve_nameref(name);
+        let val = &self.apply_case_flags(name, val);
         self.write_check(name)?;
         let layer = self.get_target_layer(name, layer);
         ArrayData::set_elem(&mut self.params[layer], name, pos, val)
     }
 
-    pub fn set_assoc_elem(&mut self, name: &str, key: &String, val: &String, layer: Option<usize>) -> Result<(), String> {
+    pub fn set_assoc_elem(&mut self, name: &str, key: &str, val: &str, layer: Option<usize>) -> Result<(), String> {
         Self::name_check(name)?;
+        let name = &self.solve_nameref(name);
+        let val = &self.apply_case_flags(name, val);
         self.write_check(name)?;
         let layer = self.get_target_layer(name, layer);
         AssocData::set_elem(&mut self.params[layer], name, key, val)
@@ -201,6 +332,8 @@ impl DataBase {
 
     pub fn set_array(&mut self, name: &str, v: Vec<String>, layer: Option<usize>) -> Result<(), String> {
         Self::name_check(name)?;
+        let name = &self.solve_nameref(name);
+        let v = v.iter().map(|e| self.apply_case_flags(name, e)).collect();
         self.write_check(name)?;
         let layer = self.get_target_layer(name, layer);
         ArrayData::set_new_entry(&mut self.params[layer], name, v)
@@ -208,6 +341,7 @@ impl DataBase {
 
     pub fn set_assoc(&mut self, name: &str, layer: Option<usize>) -> Result<(), String> {
         Self::name_check(name)?;
+        let name = &self.solve_nameref(name);
         self.write_check(name)?;
         let layer = self.get_target_layer(name, layer);
         AssocData::set_new_entry(&mut self.params[layer], name)
@@ -215,10 +349,7 @@ impl DataBase {
 
     pub fn push_local(&mut self) {
         self.params.push(HashMap::new());
-        match self.param_options.last() {
-            Some(e) => self.param_options.push(e.clone()),
-            None => exit::internal("error: DataBase::push_local"),
-        }
+        self.param_options.push(HashMap::new());
     }
 
     pub fn pop_local(&mut self) {
@@ -263,6 +394,66 @@ impl DataBase {
         setter::flag(self, name, flag)
     }
 
+    pub fn unset_flag(&mut self, name: &str, flag: char) {
+        for layer in &mut self.param_options {
+            if let Some(flags) = layer.get_mut(name) {
+                flags.retain(|c| c != flag);
+            }
+        }
+    }
+
+    pub fn get_exported_params(&mut self) -> Vec<(String, String)> {
+        let mut ans = vec![];
+        for name in self.get_keys() {
+            if self.has_flag(&name, 'x') && ! self.is_array(&name) && ! self.is_assoc(&name) {
+                let val = self.get_param(&name).unwrap_or_default();
+                ans.push((name, val));
+            }
+        }
+        ans
+    }
+
+    pub fn get_attributes(&mut self, name: &str) -> String {
+        let flags = self.get_flags(name);
+        let mut attrs = String::new();
+        if self.is_array(name) {
+            attrs.push('a');
+        }else if self.is_assoc(name) {
+            attrs.push('A');
+        }
+        "inrxlu".chars().filter(|c| flags.contains(*c)).for_each(|c| attrs.push(c));
+        attrs
+    }
+
+    pub fn get_declare_string(&mut self, name: &str) -> Option<String> {
+        let mut attrs = self.get_attributes(name);
+        if attrs.is_empty() {
+            attrs.push('-');
+        }
+
+        match getter::clone(self, name) {
+            Some(d) if d.is_special() => None,
+            Some(d) if d.is_single() => {
+                let mut d = d;
+                let val = d.get_as_single().unwrap_or_default();
+                Some(format!("declare -{} {}={}", attrs, name, ansi_c::quote(&val, Quote::Double)))
+            },
+            Some(d) => Some(format!("declare -{} {}={}", attrs, name, d.print_body())),
+            None if self.get_flags(name).is_empty() => None,
+            None => Some(format!("declare -{} {}", attrs, name)),
+        }
+    }
+
+    pub fn print_declare(&mut self, name: &str) -> bool {
+        match self.get_declare_string(name) {
+            Some(s) => {
+                println!("{}", s);
+                true
+            },
+            None => false,
+        }
+    }
+
     pub fn print(&mut self, name: &str) {
         if let Some(d) = getter::clone(self, name) {
             d.print_with_name(name);
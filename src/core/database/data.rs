@@ -47,10 +47,14 @@ pub trait Data {
         if self.is_array() {
             return self.get_as_array(pos);
         }
+        if self.is_single() && pos == "0" {
+            return self.get_as_single();
+        }
         Err("No entry".to_string())
     }
 
     fn get_all_as_array(&mut self) -> Result<Vec<String>, String> {Err("not an array".to_string())}
+    fn get_all_indexes(&mut self) -> Result<Vec<String>, String> {Err("not an array".to_string())}
 
     fn is_special(&self) -> bool {false}
     fn is_single(&self) -> bool {false}
@@ -3,6 +3,8 @@
 
 use std::collections::HashMap;
 use super::Data;
+use crate::utils::ansi_c;
+use crate::utils::ansi_c::Quote;
 
 #[derive(Debug, Clone, Default)]
 pub struct ArrayData {
@@ -26,7 +28,7 @@ impl Data for ArrayData {
         let mut formatted = String::new();
         formatted += "(";
         for i in self.keys() {
-            formatted += &format!("[{}]=\"{}\" ", i, &self.body[&i]).clone();
+            formatted += &format!("[{}]={} ", i, ansi_c::quote(&self.body[&i], Quote::Double));
         };
         if formatted.ends_with(" ") {
             formatted.pop();
@@ -56,6 +58,10 @@ impl Data for ArrayData {
         Ok(self.values().clone())
     }
 
+    fn get_all_indexes(&mut self) -> Result<Vec<String>, String> {
+        Ok(self.keys().iter().map(|k| k.to_string()).collect())
+    }
+
     fn get_as_single(&mut self) -> Result<String, String> {
         self.body.get(&0).map(|v| Ok(v.clone())).ok_or("No entry".to_string())?
     }
@@ -71,7 +77,7 @@ impl ArrayData {
     }
 
     pub fn set_elem(db_layer: &mut HashMap<String, Box<dyn Data>>,
-                        name: &str, pos: usize, val: &String) -> Result<(), String> {
+                        name: &str, pos: usize, val: &str) -> Result<(), String> {
         match db_layer.get_mut(name) {
             Some(d) => d.set_as_array(&pos.to_string(), val),
             None    => {
@@ -2,6 +2,8 @@
 //SPDXLicense-Identifier: BSD-3-Clause
 
 use super::Data;
+use crate::utils::ansi_c;
+use crate::utils::ansi_c::Quote;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, Default)]
@@ -26,7 +28,11 @@ impl Data for AssocData {
         formatted += "(";
         for k in self.keys() {
             let v = self.get(&k).unwrap_or("".to_string());
-            formatted += &format!("[{}]=\"{}\" ", k, v);
+            let k = match ! k.is_empty() && k.chars().all(|c| c.is_alphanumeric() || "_.-@%+".contains(c)) {
+                true  => k,
+                false => ansi_c::quote(&k, Quote::Double),
+            };
+            formatted += &format!("[{}]={} ", k, ansi_c::quote(&v, Quote::Double));
         }
         if formatted.ends_with(" ") {
             formatted.pop();
@@ -52,6 +58,14 @@ impl Data for AssocData {
         }
     }
 
+    fn get_all_as_array(&mut self) -> Result<Vec<String>, String> {
+        Ok(self.values())
+    }
+
+    fn get_all_indexes(&mut self) -> Result<Vec<String>, String> {
+        Ok(self.keys())
+    }
+
     fn get_as_single(&mut self) -> Result<String, String> { self.last.clone().ok_or("No last input".to_string()) }
 
     fn is_assoc(&self) -> bool {true}
@@ -65,7 +79,7 @@ impl AssocData {
     }
 
     pub fn set_elem(db_layer: &mut HashMap<String, Box<dyn Data>>, name: &str,
-                     key: &String, val: &String) -> Result<(), String> {
+                     key: &str, val: &str) -> Result<(), String> {
         match db_layer.get_mut(name) {
             Some(v) => v.set_as_assoc(key, val), 
             _ => Err("TODO".to_string()),
@@ -26,6 +26,7 @@ impl Data for SingleData {
     }
 
     fn get_as_single(&mut self) -> Result<String, String> { Ok(self.body.clone()) }
+    fn get_all_indexes(&mut self) -> Result<Vec<String>, String> { Ok(vec!["0".to_string()]) }
     fn len(&mut self) -> usize { self.body.chars().count() }
     fn is_single(&self) -> bool {true}
 }
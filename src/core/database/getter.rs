@@ -9,6 +9,7 @@ pub fn special_param(db :&DataBase, name: &str) -> Option<String> {
         "-" => db.flags.clone(),
         "?" => db.exit_status.to_string(),
         "_" => db.last_arg.clone(),
+        "!" if ! db.last_bg_pid.is_empty() => db.last_bg_pid.clone(),
         "#" => {
             let pos = db.position_parameters.len() - 1;
             (db.position_parameters[pos].len() - 1).to_string()
@@ -3,12 +3,20 @@
 
 use crate::core::DataBase;
 use crate::core::database::SpecialData;
+use crate::utils;
 use crate::utils::{random, clock};
 use std::{env, process};
 
 pub fn initialize(db: &mut DataBase) -> Result<(), String> {
     db.exit_status = 0;
 
+    for (name, val) in env::vars() {
+        if utils::is_param(&name) && name != "_" {
+            db.set_param(&name, &val, None)?;
+            db.set_flag(&name, 'x');
+        }
+    }
+
     db.set_param("$", &process::id().to_string(), None)?;
     db.set_param("BASHPID", &process::id().to_string(), None)?;
     db.set_param("BASH_SUBSHELL", "0", None)?;
@@ -28,9 +36,10 @@ pub fn initialize(db: &mut DataBase) -> Result<(), String> {
 }
 
 pub fn flag(db: &mut DataBase, name: &str, flag: char) {
-    let layer = db.position_parameters.len() - 1;
+    let layer = db.get_target_layer(name, None);
     let rf = &mut db.param_options[layer];
     match rf.get_mut(name) {
+        Some(d) if d.contains(flag) => {},
         Some(d) => d.push(flag),
         None => {rf.insert(name.to_string(), flag.to_string()); },
     }
@@ -2,76 +2,343 @@
 //SPDXLicense-Identifier: BSD-3-Clause
 
 use crate::ShellCore;
-use rev_lines::RevLines;
-use std::fs::File;
-use std::io::{BufReader, BufWriter, Write};
+use crate::utils::glob;
+use nix::errno::Errno;
+use nix::fcntl::{Flock, FlockArg};
+use std::fs;
+use std::fs::{File, Metadata};
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::fs::OpenOptions;
+use std::os::unix::fs::MetadataExt;
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Default)]
+pub struct HistoryEntry {
+    pub text: String,
+    pub time: Option<i64>,
+    pub saved: bool,
+}
+
+impl HistoryEntry {
+    pub fn new(text: &str) -> Self {
+        let time = SystemTime::now().duration_since(UNIX_EPOCH)
+                   .map(|d| d.as_secs() as i64).ok();
+        HistoryEntry { text: text.to_string(), time, saved: false }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFileMark {
+    pub offset: u64,
+    pub ino: u64,
+    pub last: String,
+}
+
+fn escape_glob(s: &str) -> String {
+    s.chars().map(|c| match "\\*?[]@+!()|".contains(c) {
+        true  => format!("\\{}", c),
+        false => c.to_string(),
+    }).collect()
+}
+
+fn file_error(filename: &str, e: io::Error) -> String {
+    match e.raw_os_error() {
+        Some(n) => format!("{}: {}", filename, Errno::from_raw(n).desc()),
+        None    => format!("{}: {}", filename, e),
+    }
+}
+
+fn to_timestamp(line: &str) -> Option<i64> {
+    match line.strip_prefix('#') {
+        Some(n) if ! n.is_empty() && n.chars().all(|c| c.is_ascii_digit()) => n.parse::<i64>().ok(),
+        _ => None,
+    }
+}
 
 impl ShellCore {
     pub fn fetch_history(&mut self, pos: usize, prev: usize, prev_str: String) -> String {
-        if prev < self.history.len() {
-            self.history[prev] = prev_str;
-        }else{
-            self.rewritten_history.insert(prev + 1 - self.history.len(), prev_str);
+        if let Some(h) = self.history.get_mut(prev) {
+            h.text = prev_str.replace("\n", "↵ \0");
+        }
+
+        match self.history.get(pos) {
+            Some(h) => h.text.clone(),
+            None    => String::new(),
+        }
+    }
+
+    fn history_size(&mut self) -> Option<usize> {
+        match self.db.get_param("HISTSIZE") {
+            Ok(s) if ! s.is_empty() => s.parse::<usize>().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn stifle_history(&mut self) -> usize {
+        let len = self.history.len();
+        if let Some(n) = self.history_size() {
+            self.history.truncate(n);
+        }
+        len - self.history.len()
+    }
+
+    pub fn add_history(&mut self, text: &str) {
+        self.history.insert(0, HistoryEntry::new(text));
+        /* as bash, only the push by the new entry moves the numbers, not a smaller HISTSIZE */
+        self.history_base += self.stifle_history().min(1);
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+        self.history_base = 0;
+    }
+
+    pub fn history_control(&mut self, text: &str) -> bool {
+        let control = self.db.get_param("HISTCONTROL").unwrap_or_default();
+        let control: Vec<&str> = control.split(':').collect();
+        let has = |c: &str| control.contains(&c) || control.contains(&"ignoreboth")
+                            && (c == "ignorespace" || c == "ignoredups");
+
+        if text.trim().is_empty() || (has("ignorespace") && text.starts_with(' ')) {
+            return false;
+        }
+
+        let text = text.trim();
+        let prev = self.history.first().map(|h| h.text.clone()).unwrap_or_default();
+        if has("ignoredups") && text == prev {
+            return false;
+        }
+
+        let ignore = self.db.get_param("HISTIGNORE").unwrap_or_default();
+        let extglob = self.shopts.query("extglob");
+        for pat in ignore.split(':').filter(|p| ! p.is_empty()) {
+            let pat = pat.replace("&", &escape_glob(&prev));
+            if glob::parse_and_compare(&text.to_string(), &pat, extglob) {
+                return false;
+            }
+        }
+
+        if has("erasedups") {
+            while let Some(pos) = self.history.iter().position(|h| h.text == text) {
+                self.history.remove(pos);
+            }
+        }
+        true
+    }
+
+    fn history_file_changed(&self, meta: &Metadata) -> bool {
+        let mark = &self.history_file;
+        mark.offset > 0 && (meta.ino() != mark.ino || meta.len() < mark.offset)
+    }
+
+    fn history_file_in_sync(&self, meta: &Metadata) -> bool {
+        ! self.history_file_changed(meta) && meta.len() == self.history_file.offset
+    }
+
+    fn mark_history_file(&mut self, meta: &Metadata, last: Option<String>) {
+        self.history_file.offset = meta.len();
+        self.history_file.ino = meta.ino();
+        if let Some(last) = last {
+            self.history_file.last = last;
+        }
+    }
+
+    fn import_history(&mut self, file: &mut File, only_new: bool) -> io::Result<()> {
+        let changed = self.history_file_changed(&file.metadata()?);
+        let from = match only_new && ! changed {
+            true  => self.history_file.offset,
+            false => 0,
+        };
+        file.seek(SeekFrom::Start(from))?;
+
+        let mut lines: Vec<String> = BufReader::new(&*file).lines().map_while(Result::ok).collect();
+        if only_new && changed {
+            let skip = lines.iter().rposition(|l| *l == self.history_file.last)
+                       .map(|p| p+1).unwrap_or(0);
+            lines.drain(..skip);
+        }
+
+        let mut entries = vec![];
+        let mut time = None;
+        for line in lines {
+            if let Some(t) = to_timestamp(&line) {
+                time = Some(t);
+                continue;
+            }
+            entries.push(HistoryEntry { text: line, time: time.take(), saved: true });
+        }
+
+        let last = entries.last().map(|h| h.text.clone());
+        if let Some(n) = self.history_size() {
+            let dropped = entries.len().saturating_sub(n);
+            entries.drain(..dropped);
+            self.history_base += dropped;
+        }
+
+        let pos = self.history.iter().take_while(|h| ! h.saved).count();
+        self.history.splice(pos..pos, entries.into_iter().rev());
+        self.history_base += self.stifle_history();
+        self.mark_history_file(&file.metadata()?, last);
+        Ok(())
+    }
+
+    fn export_history(&mut self, f: &mut impl Write, all: bool) -> io::Result<Option<String>> {
+        let stamp = self.db.has_value("HISTTIMEFORMAT");
+        let mut last = None;
+        for h in self.history.iter_mut().rev() {
+            if h.text.is_empty() || (! all && h.saved) {
+                continue;
+            }
+            h.saved = true;
+            if let (true, Some(t)) = (stamp, h.time) {
+                writeln!(f, "#{}", t)?;
+            }
+            writeln!(f, "{}", &h.text)?;
+            last = Some(h.text.clone());
         }
+        Ok(last)
+    }
 
-        if pos < self.history.len() {
-            self.history[pos].clone()
-        }else{
-            self.fetch_history_file(pos + 1 - self.history.len())
+    fn append_history(&mut self, file: &mut File) -> io::Result<()> {
+        let in_sync = self.history_file_in_sync(&file.metadata()?);
+        let mut f = BufWriter::new(&*file);
+        let last = self.export_history(&mut f, false)?;
+        f.flush()?;
+        drop(f);
+
+        if in_sync {
+            self.mark_history_file(&file.metadata()?, last);
         }
+        Ok(())
     }
 
-    pub fn fetch_history_file(&mut self, pos: usize) -> String {
-        if let Some(s) = self.rewritten_history.get(&pos) {
-            return s.to_string();
+    fn trim_history(&mut self, text: &str) -> Option<String> {
+        let max = match self.db.get_param("HISTFILESIZE").map(|s| s.parse::<usize>()) {
+            Ok(Ok(n)) => n,
+            _ => return None,
+        };
+
+        let mut entries: Vec<String> = vec![];
+        let mut stamp = String::new();
+        for line in text.lines() {
+            match to_timestamp(line) {
+                Some(_) => stamp = line.to_string() + "\n",
+                None    => entries.push(std::mem::take(&mut stamp) + line + "\n"),
+            }
         }
-        if pos == 0 {
-            return String::new();
+        match entries.len() > max {
+            true  => Some(entries[entries.len() - max..].concat()),
+            false => None,
         }
+    }
 
-        let mut file_line = pos - 1;
-        if let Ok(n) = self.db.get_param("HISTFILESIZE").unwrap_or(String::new()).parse::<usize>() {
-            file_line %= n;
+    /* rewrites go to a new file so that other sessions notice them by the inode */
+    fn replace_history_file(filename: &str, file: &File, text: &str) -> io::Result<Metadata> {
+        let tmp = format!("{}.{}.tmp", filename, process::id());
+        let result = File::create(&tmp).and_then(|mut f| {
+            f.set_permissions(file.metadata()?.permissions())?;
+            f.write_all(text.as_bytes())?;
+            fs::rename(&tmp, filename)?;
+            f.metadata()
+        });
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp);
         }
+        result
+    }
 
-        if let Ok(hist_file) = File::open(self.db.get_param("HISTFILE").unwrap_or(String::new())){
-            let mut rev_lines = RevLines::new(BufReader::new(hist_file));
-            if let Some(Ok(s)) = rev_lines.nth(file_line) {
-                return s;
+    fn overwrite_history(&mut self, filename: &str, file: &File) -> io::Result<()> {
+        let mut buf = vec![];
+        let last = self.export_history(&mut buf, true)?;
+        let text = String::from_utf8_lossy(&buf).to_string();
+        let text = self.trim_history(&text).unwrap_or(text);
+
+        let meta = Self::replace_history_file(filename, file, &text)?;
+        self.mark_history_file(&meta, last);
+        Ok(())
+    }
+
+    fn trim_history_file(&mut self, filename: &str, file: &mut File) -> io::Result<()> {
+        let in_sync = self.history_file_in_sync(&file.metadata()?);
+        let mut buf = vec![];
+        file.seek(SeekFrom::Start(0))?;
+        file.read_to_end(&mut buf)?;
+
+        if let Some(text) = self.trim_history(&String::from_utf8_lossy(&buf)) {
+            let meta = Self::replace_history_file(filename, file, &text)?;
+            if in_sync {
+                self.mark_history_file(&meta, None);
+            }
+        }
+        Ok(())
+    }
+
+    fn lock_history_file(filename: &str, write: bool) -> Result<Flock<File>, String> {
+        loop {
+            let file = match write {
+                true  => OpenOptions::new().create(true).read(true).append(true).open(filename),
+                false => File::open(filename),
+            };
+            let file = file.map_err(|e| file_error(filename, e))?;
+
+            let arg = match write {
+                true  => FlockArg::LockExclusive,
+                false => FlockArg::LockShared,
+            };
+            let file = Flock::lock(file, arg).map_err(|(_, e)| format!("{}: {}", filename, e.desc()))?;
+
+            match (file.metadata(), fs::metadata(filename)) {
+                (Ok(a), Ok(b)) if a.ino() != b.ino() => continue, //replaced while waiting
+                _ => return Ok(file),
             }
         }
+    }
 
-        String::new()
+    pub fn read_history_file(&mut self, filename: &str, only_new: bool) -> Result<(), String> {
+        let mut file = Self::lock_history_file(filename, false)?;
+        self.import_history(&mut file, only_new)
+            .map_err(|e| file_error(filename, e))
+    }
+
+    pub fn write_history(&mut self, filename: &str, append: bool) -> Result<(), String> {
+        let mut file = Self::lock_history_file(filename, true)?;
+        let result = match append {
+            true  => self.append_history(&mut file),
+            false => self.overwrite_history(filename, &file),
+        };
+        result.map_err(|e| file_error(filename, e))
+    }
+
+    pub fn sync_history(&mut self) {
+        if ! self.shopts.query("histshare") || ! self.db.flags.contains('i') || self.is_subshell {
+            return;
+        }
+        let filename = self.db.get_param("HISTFILE").unwrap_or_default();
+        if let Ok(mut file) = Self::lock_history_file(&filename, true) {
+            let _ = self.import_history(&mut file, true)
+                    .and_then(|_| self.append_history(&mut file));
+        }
     }
 
     pub fn write_history_to_file(&mut self) {
         if ! self.db.flags.contains('i') || self.is_subshell {
             return;
         }
-        let filename = self.db.get_param("HISTFILE").unwrap_or(String::new());
-        if filename == "" {
+        let filename = self.db.get_param("HISTFILE").unwrap_or_default();
+        if filename.is_empty() {
             eprintln!("sush: HISTFILE is not set");
             return;
         }
-    
-        let file = match OpenOptions::new().create(true)
-                .write(true).append(true).open(&filename) {
-            Ok(f) => f,
-            _     => {
-                eprintln!("sush: invalid history file");
-                return;
-            },
-        };
-    
-        let mut f = BufWriter::new(file);
-        for h in self.history.iter().rev() {
-            if h == "" {
-                continue;
-            }
-            let _ = f.write(h.as_bytes());
-            let _ = f.write(&[0x0A]);
+
+        let result = Self::lock_history_file(&filename, true).and_then(|mut file| {
+            self.append_history(&mut file)
+                .and_then(|_| self.trim_history_file(&filename, &mut file))
+                .map_err(|e| e.to_string())
+        });
+        if result.is_err() {
+            eprintln!("sush: invalid history file");
         }
-        let _ = f.flush();
     }
 }
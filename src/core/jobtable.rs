@@ -7,6 +7,8 @@ use nix::unistd::Pid;
 use nix::sys::signal;
 use nix::sys::wait::{waitpid, WaitPidFlag, WaitStatus};
 
+const BG_EXIT_STATUSES_MAX: usize = 1024;
+
 #[derive(Debug)]
 pub struct JobEntry {
     pub id: usize,
@@ -14,7 +16,9 @@ pub struct JobEntry {
     proc_statuses: Vec<WaitStatus>,
     pub display_status: String,
     pub text: String,
+    pub no_hup: bool,
     change: bool,
+    inherited: bool,
 }
 
 fn wait_nonblock(pid: &Pid, status: &mut WaitStatus) {
@@ -34,17 +38,20 @@ fn wait_nonblock(pid: &Pid, status: &mut WaitStatus) {
     }
 }
 
+fn to_exit_status(status: &WaitStatus) -> i32 {
+    match status {
+        WaitStatus::Exited(_, es) => *es,
+        WaitStatus::Stopped(_, _) => 148,
+        WaitStatus::Signaled(_, sig, _) => *sig as i32 + 128,
+        _ => 1,
+    }
+}
+
 fn wait_block(pid: &Pid, status: &mut WaitStatus) -> i32 {
     match waitpid(*pid, Some(WaitPidFlag::WUNTRACED)) {
         Ok(s) => {
             *status = s;
-
-            return match status {
-                WaitStatus::Exited(_, es) => *es,
-                WaitStatus::Stopped(_, _) => 148,
-                WaitStatus::Signaled(_, sig, _) => *sig as i32 + 128,
-                _ => 1,
-            };
+            to_exit_status(status)
         },
         _  => panic!("SUSHI INTERNAL ERROR (wrong pid wait)"),
     }
@@ -68,11 +75,17 @@ impl JobEntry {
             proc_statuses: statuses.to_vec(),
             display_status: status.to_string(),
             text: text.to_string(),
+            no_hup: false,
             change: false,
+            inherited: false,
         }
     }
 
     pub fn update_status(&mut self, wait: bool) -> i32 {
+        if self.inherited { // not a child of this process
+            return 127;
+        }
+
         let mut exit_status = 0;
         let before = self.proc_statuses[0];
         for (status, pid) in self.proc_statuses.iter_mut().zip(&self.pids) {
@@ -106,13 +119,46 @@ impl JobEntry {
         exit_status
     }
 
-    pub fn print(&self, priority: &Vec<usize>) {
+    pub fn is_done(&self) -> bool {
+        ! self.proc_statuses.iter().any(still)
+    }
+
+    pub fn exit_status(&self) -> i32 {
+        self.proc_statuses.last().map(to_exit_status).unwrap_or(1)
+    }
+
+    pub fn pid_status(&self, pid: i32) -> Option<i32> {
+        self.pids.iter().position(|p| p.as_raw() == pid)
+            .map(|i| to_exit_status(&self.proc_statuses[i]))
+    }
+
+    pub fn take_change(&mut self) -> bool {
+        std::mem::take(&mut self.change)
+    }
+
+    pub fn pids(&self) -> &[Pid] {
+        &self.pids
+    }
+
+    fn mark(&self, priority: &Vec<usize>) -> char {
         if priority[0] == self.id {
-            println!("[{}]+  {}     {}", self.id, &self.display_status, &self.text);
+            '+'
         }else if priority.len() > 1 && priority[1] == self.id {
-            println!("[{}]-  {}     {}", self.id, &self.display_status, &self.text);
+            '-'
         }else {
-            println!("[{}]   {}     {}", self.id, &self.display_status, &self.text);
+            ' '
+        }
+    }
+
+    pub fn print(&self, priority: &Vec<usize>) {
+        println!("[{}]{}  {}     {}", self.id, self.mark(priority), &self.display_status, &self.text);
+    }
+
+    pub fn print_with_pids(&self, priority: &Vec<usize>) {
+        println!("[{}]{} {} {}     {}", self.id, self.mark(priority), self.pids[0],
+                 &self.display_status, &self.text);
+        for pid in &self.pids[1..] {
+            println!("     {}", pid);
         }
     }
 
@@ -168,6 +214,40 @@ impl JobEntry {
         }
     }
 
+    pub fn send_signal(&mut self, sig: Option<signal::Signal>) -> Result<(), String> {
+        let pgid = self.solve_pgid();
+        let result = match pgid.as_raw() == 0 || pgid == unistd::getpgrp() {
+            true  => self.pids.iter().try_for_each(|pid| signal::kill(*pid, sig)),
+            false => signal::kill(Pid::from_raw(-pgid.as_raw()), sig),
+        };
+
+        if let Err(e) = result {
+            return Err(format!("({}) - {}", self.pids[0], e.desc()));
+        }
+
+        if self.is_stopped()
+        && (sig == Some(signal::SIGTERM) || sig == Some(signal::SIGHUP)) {
+            self.send_cont();
+        }
+        Ok(())
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.display_status == "Running"
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.display_status == "Stopped"
+    }
+
+    pub fn is_inherited(&self) -> bool {
+        self.inherited
+    }
+
+    pub fn has_pid(&self, pid: i32) -> bool {
+        self.pids.iter().any(|p| p.as_raw() == pid)
+    }
+
     pub fn solve_pgid(&self) -> Pid {
         for pid in &self.pids {
             match unistd::getpgid(Some(*pid)) {
@@ -194,12 +274,50 @@ impl ShellCore {
             }
         }
 
+        let done: Vec<(i32, i32)> = self.job_table.iter().filter(|e| e.is_done())
+            .flat_map(|e| e.pids.iter().zip(e.proc_statuses.iter()))
+            .map(|(pid, status)| (pid.as_raw(), to_exit_status(status)))
+            .collect();
+        for (pid, status) in done {
+            self.bg_exit_statuses.retain(|(p, _)| *p != pid);
+            self.bg_exit_statuses.push((pid, status));
+        }
+        if self.bg_exit_statuses.len() > BG_EXIT_STATUSES_MAX {
+            let over = self.bg_exit_statuses.len() - BG_EXIT_STATUSES_MAX;
+            self.bg_exit_statuses.drain(..over);
+        }
+
         self.job_table.retain(|e| still(&e.proc_statuses[0]) || e.display_status == "Stopped");
 
         let ids = self.job_table.iter().map(|j| j.id).collect::<Vec<usize>>();
         self.job_table_priority.retain(|id| ids.contains(id) );
     }
 
+    pub fn jobtable_remove(&mut self, id: usize) {
+        if let Some(job) = self.job_table.iter().find(|j| j.id == id) {
+            self.bg_exit_statuses.retain(|(p, _)| ! job.has_pid(*p));
+        }
+        self.job_table.retain(|j| j.id != id);
+        self.job_table_priority.retain(|i| *i != id);
+    }
+
+    pub fn take_bg_exit_status(&mut self, pid: i32) -> Option<i32> {
+        let pos = self.bg_exit_statuses.iter().position(|(p, _)| *p == pid)?;
+        Some(self.bg_exit_statuses.remove(pos).1)
+    }
+
+    /* jobs of the parent stay visible to command substitutions and pipelines */
+    pub fn jobtable_init_as_subshell(&mut self, keep: bool) {
+        match keep {
+            true  => self.job_table.iter_mut().for_each(|j| j.inherited = true),
+            false => {
+                self.job_table.clear();
+                self.job_table_priority.clear();
+            },
+        }
+        self.bg_exit_statuses.clear();
+    }
+
     pub fn generate_new_job_id(&self) -> usize {
         match self.job_table.last() {
             None      => 1,
@@ -209,6 +327,37 @@ impl ShellCore {
 }
 
 impl ShellCore {
+    pub fn jobtable_hangup(&mut self) {
+        for job in self.job_table.iter_mut().filter(|j| ! j.no_hup) {
+            let _ = job.send_signal(Some(signal::SIGHUP));
+        }
+    }
+
+    /* returns true when the exit should be deferred */
+    pub fn jobtable_check_before_exit(&mut self) -> bool {
+        if self.is_subshell || ! self.db.flags.contains('i') || self.exit_warned {
+            return false;
+        }
+
+        self.jobtable_check_status();
+        let checkjobs = self.shopts.query("checkjobs");
+        if self.job_table.iter().any(|j| j.is_stopped()) {
+            eprintln!("There are stopped jobs.");
+        }else if checkjobs && self.job_table.iter().any(|j| j.is_running()) {
+            eprintln!("There are running jobs.");
+        }else{
+            return false;
+        }
+
+        if checkjobs {
+            for job in self.job_table.iter() {
+                job.print(&self.job_table_priority);
+            }
+        }
+        self.exit_warned = true;
+        true
+    }
+
     pub fn get_stopped_job_commands(&self) -> Vec<String> {
         self.job_table.iter().map(|j| j.text.split(' ').nth(0).unwrap().to_string()).collect()
     }
@@ -3,6 +3,12 @@
 
 use std::collections::HashMap;
 
+pub const FLAG_OPTIONS: [(char, &str); 11] = [
+    ('a', "allexport"), ('B', "braceexpand"), ('C', "noclobber"),
+    ('e', "errexit"), ('f', "noglob"), ('h', "hashall"), ('m', "monitor"),
+    ('n', "noexec"), ('u', "nounset"), ('v', "verbose"), ('x', "xtrace"),
+];
+
 #[derive(Debug, Default)]
 pub struct Options {
     opts: HashMap<String, bool>,
@@ -11,8 +17,11 @@ pub struct Options {
 impl Options {
     pub fn new_as_basic_opts() -> Options {
         let mut options = Options::default();
+        for (_, opt) in FLAG_OPTIONS {
+            options.opts.insert(opt.to_string(), false);
+        }
         options.opts.insert("pipefail".to_string(), false);
-        options.opts.insert("noglob".to_string(), false);
+        options.opts.insert("braceexpand".to_string(), true);
         options
     }
 
@@ -24,7 +33,7 @@ impl Options {
                    "dotglob", "execfail", "expand_aliases", "extdebug",
                    "extglob", "extquote", "failglob", "force_fignore",
                    "globstar", "gnu_errfmt", "histappend", "histreedit",
-                   "histverify", "hostcomplete", "huponexit", "interactive_comments",
+                   "histshare", "histverify", "hostcomplete", "huponexit", "interactive_comments",
                    "lastpipe", "lithist", "login_shell", "mailwarn",
                    "no_empty_cmd_completion", "nocaseglob", "nocasematch", "nullglob",
                    "promptvars", "restricted_shell", "shift_verbose",
@@ -118,6 +127,16 @@ impl Options {
         true
     }
 
+    pub fn get_enabled(&self) -> Vec<String> {
+        let mut list = self.opts.iter()
+                       .filter(|opt| *opt.1)
+                       .map(|opt| opt.0.clone())
+                       .collect::<Vec<String>>();
+
+        list.sort();
+        list
+    }
+
     pub fn get_keys(&self) -> Vec<String> {
         self.opts.clone().into_keys().collect()
     }
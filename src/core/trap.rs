@@ -0,0 +1,109 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{signal, Feeder, Script, ShellCore};
+use nix::sys::signal::Signal;
+use signal_hook::consts;
+
+impl ShellCore {
+    pub fn set_trap(&mut self, name: &str, command: &str) {
+        if let Some(sig) = signal::to_signal(name) {
+            let num = sig as i32;
+            if consts::FORBIDDEN.contains(&num) {
+                return;
+            }
+            if self.signal_handle.is_none() { //the thread doesn't survive fork
+                signal::run_signal_check(self);
+            }
+            if let Some(h) = self.signal_handle.as_ref() {
+                let _ = h.add_signal(num);
+            }
+            self.trapped_signals.lock().unwrap().insert(num);
+        }
+
+        self.traps.insert(name.to_string(), command.to_string());
+    }
+
+    pub fn unset_trap(&mut self, name: &str) {
+        if let Some(sig) = signal::to_signal(name) {
+            self.trapped_signals.lock().unwrap().remove(&(sig as i32));
+        }
+        self.traps.remove(name);
+    }
+
+    pub fn run_trap(&mut self, name: &str) {
+        if self.in_trap {
+            return;
+        }
+
+        let command = match self.traps.get(name) {
+            Some(c) if ! c.is_empty() => c.clone(),
+            _ => return,
+        };
+
+        let exit_status = self.db.exit_status;
+        let lineno = self.db.get_param("LINENO").unwrap_or_default();
+        self.in_trap = true;
+        let mut feeder = Feeder::new(&command);
+        feeder.lineno = lineno.parse::<usize>().unwrap_or(1);
+        if let Some(mut s) = Script::parse(&mut feeder, self, false) {
+            s.exec(self);
+        }
+        self.in_trap = false;
+        let _ = self.db.set_param("LINENO", &lineno, None);
+        self.db.exit_status = exit_status;
+    }
+
+    pub fn run_exit_trap(&mut self) {
+        self.run_trap("EXIT");
+        self.traps.remove("EXIT");
+    }
+
+    fn in_function(&self) -> bool {
+        self.source_function_level > self.source_level
+    }
+
+    pub fn run_err_trap(&mut self) {
+        if self.db.exit_status != 0
+        && ! self.suspend_e_option
+        && ! self.in_function() {
+            self.run_trap("ERR");
+        }
+    }
+
+    pub fn run_debug_trap(&mut self) {
+        if ! self.in_function() {
+            self.run_trap("DEBUG");
+        }
+    }
+
+    pub fn check_caught_signals(&mut self) {
+        let nums: Vec<i32> = self.caught_signals.lock().unwrap().drain(..).collect();
+
+        for n in nums {
+            if let Ok(sig) = Signal::try_from(n) {
+                self.run_trap(sig.as_str());
+            }
+        }
+    }
+
+    pub fn reset_traps_as_subshell(&mut self) {
+        let mut trapped = self.trapped_signals.lock().unwrap();
+        for (name, command) in self.traps.iter() {
+            if let Some(sig) = signal::to_signal(name) {
+                match command.as_str() {
+                    "" => signal::ignore(sig),
+                    _  => {
+                        signal::restore(sig);
+                        trapped.remove(&(sig as i32));
+                    },
+                }
+            }
+        }
+        drop(trapped);
+
+        self.traps.retain(|_, c| c.is_empty());
+        self.caught_signals.lock().unwrap().clear();
+        self.in_trap = false;
+    }
+}
@@ -3,6 +3,7 @@
 
 use crate::{ShellCore, Feeder};
 use crate::elements::command;
+use super::expr::arithmetic::ArithmeticExpr;
 use super::word::Word;
 
 #[derive(Debug, Clone, Default)]
@@ -28,6 +29,61 @@ impl Array {
         Ok(ans)
     }
 
+    fn split_key(text: &str) -> Option<(&str, &str)> {
+        if ! text.starts_with('[') {
+            return None;
+        }
+
+        let (mut quote, mut escaped) = (None, false);
+        for (i, c) in text.char_indices() {
+            match (quote, c) {
+                _ if escaped => escaped = false,
+                (q, '\\') if q != Some('\'') => escaped = true,
+                (None, '\'') | (None, '"') => quote = Some(c),
+                (Some(q), _) if q == c => quote = None,
+                (None, ']') if text[i+1..].starts_with('=') => return Some((&text[1..i], &text[i+2..])),
+                _ => {},
+            }
+        }
+        None
+    }
+
+    pub fn has_keys(&self) -> bool {
+        self.words.iter().any(|w| Self::split_key(&w.text).is_some())
+    }
+
+    fn eval_index(key: &str, core: &mut ShellCore) -> Result<String, String> {
+        let mut feeder = Feeder::new(key);
+        match ArithmeticExpr::parse(&mut feeder, core, false) {
+            Some(mut a) if feeder.len() == 0 => a.eval(core),
+            _ => Err(format!("{}: bad array subscript", key)),
+        }
+    }
+
+    /* elements in the form of [key]=value are returned with their keys */
+    pub fn eval_with_keys(&mut self, core: &mut ShellCore, assoc: bool)
+                          -> Result<Vec<(Option<String>, String)>, String> {
+        let mut ans = vec![];
+
+        for w in &mut self.words {
+            let (key, value) = match Self::split_key(&w.text) {
+                Some(kv) => kv,
+                None => {
+                    ans.extend(w.eval(core)?.into_iter().map(|v| (None, v)));
+                    continue;
+                },
+            };
+
+            let key = match assoc {
+                true  => Word::eval_without_split(key, core)?,
+                false => Self::eval_index(key, core)?,
+            };
+            ans.push((Some(key), Word::eval_without_split(value, core)?));
+        }
+
+        Ok(ans)
+    }
+
     fn eat_word(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
         if feeder.starts_with(")") {
             return false;
@@ -10,6 +10,8 @@ pub mod r#for;
 pub mod test;
 pub mod function_def;
 pub mod r#while;
+pub mod select;
+pub mod coproc;
 pub mod r#if;
 
 use crate::{proc_ctrl, ShellCore, Feeder, Script};
@@ -21,6 +23,8 @@ use self::paren::ParenCommand;
 use self::brace::BraceCommand;
 use self::function_def::FunctionDefinition;
 use self::r#while::WhileCommand;
+use self::select::SelectCommand;
+use self::coproc::CoprocCommand;
 use self::r#for::ForCommand;
 use self::r#if::IfCommand;
 use self::test::TestCommand;
@@ -57,6 +61,7 @@ pub trait Command {
         match unsafe{unistd::fork()} {
             Ok(ForkResult::Child) => {
                 core.initialize_as_subshell(Pid::from_raw(0), pipe.pgid);
+                core.jobtable_init_as_subshell(pipe.is_connected());
                 io::connect(pipe, self.get_redirects(), core);
                 self.run(core, true);
                 exit::normal(core)
@@ -141,6 +146,8 @@ pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Box<dyn Comman
     else if let Some(a) = BraceCommand::parse(feeder, core) { Some(Box::new(a)) }
     else if let Some(a) = ForCommand::parse(feeder, core) { Some(Box::new(a)) }
     else if let Some(a) = WhileCommand::parse(feeder, core) { Some(Box::new(a)) }
+    else if let Some(a) = SelectCommand::parse(feeder, core) { Some(Box::new(a)) }
+    else if let Some(a) = CoprocCommand::parse(feeder, core) { Some(Box::new(a)) }
     else if let Some(a) = CaseCommand::parse(feeder, core) { Some(Box::new(a)) }
     else if let Some(a) = TestCommand::parse(feeder, core) { Some(Box::new(a)) }
     else{ None }
@@ -0,0 +1,169 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{proc_ctrl, ShellCore, Feeder};
+use crate::core::jobtable::JobEntry;
+use crate::utils::{error, exit};
+use super::{Command, Pipe, Redirect};
+use crate::elements::{command, io};
+use nix::fcntl;
+use nix::sys::wait::WaitStatus;
+use nix::unistd;
+use nix::unistd::{ForkResult, Pid};
+use std::os::fd::IntoRawFd;
+use std::os::unix::prelude::RawFd;
+
+#[derive(Debug, Clone, Default)]
+pub struct CoprocCommand {
+    text: String,
+    name: String,
+    command: Option<Box<dyn Command>>,
+    redirects: Vec<Redirect>,
+}
+
+impl Command for CoprocCommand {
+    fn exec(&mut self, core: &mut ShellCore, pipe: &mut Pipe) -> Option<Pid> {
+        let (to_coproc_recv, to_coproc_send) = Self::pipe();
+        let (from_coproc_recv, from_coproc_send) = Self::pipe();
+
+        let backup = core.tty_fd.as_ref().map(|fd| fd.try_clone().unwrap());
+        core.tty_fd = None;
+
+        let pid = match unsafe{unistd::fork()} {
+            Ok(ForkResult::Child) => {
+                core.initialize_as_subshell(Pid::from_raw(0), pipe.pgid);
+                core.jobtable_init_as_subshell(false);
+                io::close(to_coproc_send, "Cannot close coproc pipe");
+                io::close(from_coproc_recv, "Cannot close coproc pipe");
+                io::replace(to_coproc_recv, 0);
+                io::replace(from_coproc_send, 1);
+                self.run(core, true);
+                exit::normal(core)
+            },
+            Ok(ForkResult::Parent { child } ) => {
+                proc_ctrl::set_pgid(core, child, pipe.pgid);
+                child
+            },
+            Err(err) => panic!("sush(fatal): Failed to fork. {}", err),
+        };
+        core.tty_fd = backup;
+
+        io::close(to_coproc_recv, "Cannot close coproc pipe");
+        io::close(from_coproc_send, "Cannot close coproc pipe");
+        let fds = vec![Self::move_fd(from_coproc_recv), Self::move_fd(to_coproc_send)];
+        self.set_variables(core, &fds, pid);
+        core.db.last_bg_pid = pid.to_string();
+
+        let new_job_id = core.generate_new_job_id();
+        core.job_table_priority.insert(0, new_job_id);
+        core.job_table.push(JobEntry::new(vec![Some(pid)], &vec![ WaitStatus::StillAlive ],
+                &self.text, "Running", new_job_id));
+
+        core.db.exit_status = 0;
+        None
+    }
+
+    fn run(&mut self, core: &mut ShellCore, fork: bool) {
+        if ! fork {
+            exit::internal(" (no fork for coproc)");
+        }
+
+        let mut pipe = Pipe::end(-1, unistd::getpgrp());
+        let pid = match self.command {
+            Some(ref mut c) => c.exec(core, &mut pipe),
+            _ => exit::internal(" (CoprocCommand::run)"),
+        };
+        proc_ctrl::wait_pipeline(core, vec![pid], false, false);
+    }
+
+    fn get_text(&self) -> String { self.text.clone() }
+    fn get_redirects(&mut self) -> &mut Vec<Redirect> { &mut self.redirects }
+    fn set_force_fork(&mut self) { }
+    fn boxed_clone(&self) -> Box<dyn Command> {Box::new(self.clone())}
+    fn force_fork(&self) -> bool { true }
+}
+
+impl CoprocCommand {
+    fn pipe() -> (RawFd, RawFd) {
+        let (recv, send) = unistd::pipe().expect("Cannot open pipe");
+        (recv.into_raw_fd(), send.into_raw_fd())
+    }
+
+    fn move_fd(fd: RawFd) -> RawFd {
+        match fcntl::fcntl(fd, fcntl::F_DUPFD_CLOEXEC(10)) {
+            Ok(newfd) => {
+                io::close(fd, "Cannot close coproc pipe");
+                newfd
+            },
+            _ => fd,
+        }
+    }
+
+    fn set_variables(&mut self, core: &mut ShellCore, fds: &[RawFd], pid: Pid) {
+        let fds = fds.iter().map(|fd| fd.to_string()).collect();
+        let pid_name = self.name.clone() + "_PID";
+
+        if let Err(e) = core.db.set_array(&self.name, fds, None) {
+            error::print(&format!("{}: {}", &self.name, e), core);
+        }
+        if let Err(e) = core.db.set_param(&pid_name, &pid.to_string(), None) {
+            error::print(&format!("{}: {}", &pid_name, e), core);
+        }
+    }
+
+    fn starts_with_compound(feeder: &mut Feeder) -> bool {
+        ["{", "(", "[[", "while", "until", "for", "select", "if", "case"]
+            .iter().any(|s| feeder.starts_with(s))
+    }
+
+    fn eat_name(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        let len = feeder.scanner_name(core);
+        if len == 0 {
+            return false;
+        }
+
+        feeder.set_backup();
+        let name = feeder.consume(len);
+        let mut blank = String::new();
+        if command::eat_blank_with_comment(feeder, core, &mut blank)
+        && Self::starts_with_compound(feeder) {
+            feeder.pop_backup();
+            ans.text += &(name.clone() + &blank);
+            ans.name = name;
+            return true;
+        }
+
+        feeder.rewind();
+        false
+    }
+
+    pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Self> {
+        if ! feeder.starts_with("coproc") {
+            return None;
+        }
+        feeder.set_backup();
+
+        let mut ans = Self { text: feeder.consume(6), ..Default::default() };
+        if ! command::eat_blank_with_comment(feeder, core, &mut ans.text) {
+            feeder.rewind();
+            return None;
+        }
+
+        if ! Self::eat_name(feeder, &mut ans, core) {
+            ans.name = "COPROC".to_string();
+        }
+
+        match command::parse(feeder, core) {
+            Some(c) => {
+                feeder.pop_backup();
+                ans.text += &c.get_text();
+                ans.command = Some(c);
+                Some(ans)
+            },
+            None => {
+                feeder.rewind();
+                None
+            },
+        }
+    }
+}
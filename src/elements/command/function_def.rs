@@ -9,7 +9,7 @@ use nix::unistd::Pid;
 
 fn reserved(w: &str) -> bool {
     match w {
-        "{" | "}" | "while" | "do" | "done" | "if" | "then" | "elif" | "else" | "fi" => true,
+        "{" | "}" | "while" | "until" | "do" | "done" | "if" | "then" | "elif" | "else" | "fi" => true,
         _ => false,
     }
 }
@@ -64,12 +64,19 @@ impl FunctionDefinition {
 
         let mut dummy = Pipe::new("|".to_string());
 
+        let return_trap = core.traps.get("RETURN").cloned();
         core.source_function_level += 1;
+        let layer = core.db.get_layer_num() - 1;
+        core.db.function_layers.push(layer);
         let pid = self.command.clone()
                         .unwrap()
                         .exec(core, &mut dummy);
         core.return_flag = false;
+        if core.traps.get("RETURN") != return_trap.as_ref() {
+            core.run_trap("RETURN");
+        }
         core.source_function_level -= 1;
+        core.db.function_layers.pop();
 
         core.db.position_parameters.pop();
 
@@ -0,0 +1,221 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{ShellCore, Feeder, Script};
+use super::{Command, Redirect};
+use crate::elements::command;
+use crate::elements::word::Word;
+use crate::utils::error;
+use nix::unistd;
+use std::sync::atomic::Ordering::Relaxed;
+
+#[derive(Debug, Clone, Default)]
+pub struct SelectCommand {
+    text: String,
+    name: String,
+    has_in: bool,
+    values: Vec<Word>,
+    do_script: Option<Script>,
+    redirects: Vec<Redirect>,
+    force_fork: bool,
+}
+
+impl Command for SelectCommand {
+    fn run(&mut self, core: &mut ShellCore, _: bool) {
+        core.loop_level += 1;
+
+        if ! self.run_with_values(core) && core.db.exit_status == 0 {
+            core.db.exit_status = 1;
+        }
+
+        core.loop_level -= 1;
+        if core.loop_level == 0 {
+            core.break_counter = 0;
+        }
+    }
+
+    fn get_text(&self) -> String { self.text.clone() }
+    fn get_redirects(&mut self) -> &mut Vec<Redirect> { &mut self.redirects }
+    fn set_force_fork(&mut self) { self.force_fork = true; }
+    fn boxed_clone(&self) -> Box<dyn Command> {Box::new(self.clone())}
+    fn force_fork(&self) -> bool { self.force_fork }
+}
+
+impl SelectCommand {
+    fn eval_values(&mut self, core: &mut ShellCore) -> Option<Vec<String>> {
+        let mut ans = vec![];
+        for w in &mut self.values {
+            match w.eval(core) {
+                Ok(mut ws) => ans.append(&mut ws),
+                Err(e)     => {
+                    error::print(&e, core);
+                    return None;
+                },
+            }
+        }
+
+        Some(ans)
+    }
+
+    fn print_menu(values: &[String]) {
+        let width = values.len().to_string().len();
+        for (i, v) in values.iter().enumerate() {
+            eprintln!("{:>w$}) {}", i+1, v, w = width);
+        }
+    }
+
+    fn read_reply() -> Option<String> {
+        let mut line = vec![];
+        let mut ch = [0; 1];
+        loop {
+            match unistd::read(0, &mut ch) {
+                Ok(1) if ch[0] == b'\n' => break,
+                Ok(1) => line.push(ch[0]),
+                _ if line.is_empty() => return None,
+                _ => break,
+            }
+        }
+        Some(String::from_utf8_lossy(&line).to_string())
+    }
+
+    fn run_with_values(&mut self, core: &mut ShellCore) -> bool {
+        let values = match self.has_in {
+            true  => match self.eval_values(core) {
+                Some(vs) => vs,
+                None     => return false,
+            },
+            false => core.db.get_position_params(),
+        };
+
+        if values.is_empty() {
+            return true;
+        }
+
+        Self::print_menu(&values);
+        loop {
+            if core.sigint.load(Relaxed) {
+                return false;
+            }
+
+            let ps3 = match core.db.get_param("PS3") {
+                Ok(p) if ! p.is_empty() => p,
+                _ => "#? ".to_string(),
+            };
+            eprint!("{}", ps3);
+
+            let reply = match Self::read_reply() {
+                Some(r) => r,
+                None => {
+                    eprintln!();
+                    core.db.exit_status = 1;
+                    return true;
+                },
+            };
+
+            if reply.is_empty() {
+                Self::print_menu(&values);
+                continue;
+            }
+
+            let value = match reply.trim().parse::<usize>() {
+                Ok(n) if n >= 1 && n <= values.len() => values[n-1].clone(),
+                _ => String::new(),
+            };
+
+            if let Err(e) = core.db.set_param("REPLY", &reply, None)
+                            .and_then(|_| core.db.set_param(&self.name, &value, None)) {
+                core.db.exit_status = 1;
+                error::print(&e, core);
+            }
+
+            self.do_script.as_mut().unwrap().exec(core);
+
+            if core.break_counter > 0 {
+                core.break_counter -= 1;
+                break;
+            }
+            if core.continue_counter > 0 {
+                core.continue_counter -= 1;
+            }
+        }
+        true
+    }
+
+    fn eat_name(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        command::eat_blank_with_comment(feeder, core, &mut ans.text);
+
+        let len = feeder.scanner_name(core);
+        if len == 0 {
+            return false;
+        }
+
+        ans.name = feeder.consume(len);
+        ans.text += &ans.name.clone();
+        command::eat_blank_with_comment(feeder, core, &mut ans.text);
+        true
+    }
+
+    fn eat_in_part(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) {
+        if ! feeder.starts_with("in") {
+            return;
+        }
+
+        ans.text += &feeder.consume(2);
+        ans.has_in = true;
+
+        loop {
+            command::eat_blank_with_comment(feeder, core, &mut ans.text);
+            match Word::parse(feeder, core, false) {
+                Some(w) => {
+                    ans.text += &w.text.clone();
+                    ans.values.push(w);
+                },
+                None    => return,
+            }
+        }
+    }
+
+    fn eat_end(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        command::eat_blank_with_comment(feeder, core, &mut ans.text);
+        if feeder.starts_with(";") || feeder.starts_with("\n") {
+            ans.text += &feeder.consume(1);
+            command::eat_blank_with_comment(feeder, core, &mut ans.text);
+            true
+        }else{
+            false
+        }
+    }
+
+    pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Self> {
+        if ! feeder.starts_with("select") {
+            return None;
+        }
+        let mut ans = Self { text: feeder.consume(6), ..Default::default() };
+
+        if ! Self::eat_name(feeder, &mut ans, core) {
+            return None;
+        }
+        Self::eat_in_part(feeder, &mut ans, core);
+
+        if ! Self::eat_end(feeder, &mut ans, core) {
+            return None;
+        }
+
+        if feeder.len() == 0 && ! feeder.feed_additional_line(core) {
+            return None;
+        }
+
+        while command::eat_blank_with_comment(feeder, core, &mut ans.text) {}
+
+        if command::eat_inner_script(feeder, core, "do", vec!["done"],  &mut ans.do_script, false) {
+            ans.text.push_str("do");
+            ans.text.push_str(&ans.do_script.as_mut().unwrap().get_text());
+            ans.text.push_str(&feeder.consume(4)); //done
+
+            command::eat_redirects(feeder, core, &mut ans.redirects, &mut ans.text);
+            Some(ans)
+        }else{
+            None
+        }
+    }
+}
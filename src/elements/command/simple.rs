@@ -31,6 +31,7 @@ impl Command for SimpleCommand {
         if Self::break_continue_or_return(core) {
             return None;
         }
+        core.run_debug_trap();
 
         self.args.clear();
         let mut words = self.words.to_vec();
@@ -89,6 +90,11 @@ impl SimpleCommand {
         core.db.last_arg = self.args.last().unwrap().clone();
         self.option_x_output(core);
 
+        if self.is_redirect_only_exec(core) && ! self.force_fork && ! pipe.is_connected() {
+            self.exec_redirects(core);
+            return None;
+        }
+
         if self.force_fork 
         || pipe.is_connected() 
         || ( ! core.builtins.contains_key(&self.args[0]) 
@@ -100,6 +106,18 @@ impl SimpleCommand {
         }
     }
 
+    fn is_redirect_only_exec(&self, core: &mut ShellCore) -> bool {
+        self.args.len() == 1 && self.args[0] == "exec"
+        && ! core.db.functions.contains_key("exec")
+    }
+
+    fn exec_redirects(&mut self, core: &mut ShellCore) {
+        core.db.exit_status = match self.redirects.iter_mut().all(|r| r.connect(false, core)) {
+            true  => 0,
+            false => 1,
+        };
+    }
+
     fn check_sigint(core: &mut ShellCore) -> bool {
         if core.sigint.load(Relaxed) {
             core.db.exit_status = 130;
@@ -112,8 +130,9 @@ impl SimpleCommand {
         core.db.last_arg = String::new();
         self.option_x_output(core);
         
-        self.substitutions.iter_mut()
-            .for_each(|s| {s.eval(core, None, false);});
+        if ! self.substitutions.iter_mut().all(|s| s.eval(core, None, false)) {
+            core.word_eval_error = true;
+        }
 
         None
     }
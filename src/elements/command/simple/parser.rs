@@ -32,7 +32,7 @@ impl SimpleCommand {
         if ans.words.is_empty() {
             if utils::reserved(&w.text) {
                 return false;
-            }else if w.text == "local" || w.text == "eval" {
+            }else if ["local", "eval", "declare", "typeset", "export", "readonly"].contains(&w.text.as_str()) {
                 ans.permit_substitution_arg = true;
             }
         }
@@ -80,7 +80,7 @@ impl SimpleCommand {
     }
 
     pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<SimpleCommand> {
-        let mut ans = Self::default();
+        let mut ans = Self { lineno: feeder.lineno, ..Default::default() };
         feeder.set_backup();
 
         while Self::eat_substitution(feeder, &mut ans, core) {
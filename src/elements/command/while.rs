@@ -12,17 +12,19 @@ pub struct WhileCommand {
     pub do_script: Option<Script>,
     pub redirects: Vec<Redirect>,
     force_fork: bool,
+    until: bool,
 }
 
 impl Command for WhileCommand {
     fn run(&mut self, core: &mut ShellCore, _: bool) {
         core.loop_level += 1;
         loop {
+            let suspend_e_option = core.suspend_e_option;
             core.suspend_e_option = true;
             self.while_script.as_mut().unwrap().exec(core);
 
-            core.suspend_e_option = false;
-            if core.db.exit_status != 0 {
+            core.suspend_e_option = suspend_e_option;
+            if (core.db.exit_status != 0) != self.until {
                 core.db.exit_status = 0;
                 break;
             }
@@ -54,15 +56,20 @@ impl Command for WhileCommand {
 
 impl WhileCommand {
     pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<WhileCommand> {
-        let mut ans = Self::default();
-        if ! command::eat_inner_script(feeder, core, "while", vec!["do"],
+        let mut ans = Self { until: feeder.starts_with("until"), ..Default::default() };
+        let keyword = match ans.until {
+            true  => "until",
+            false => "while",
+        };
+
+        if ! command::eat_inner_script(feeder, core, keyword, vec!["do"],
                                        &mut ans.while_script, false){
             return None;
         }
         while command::eat_blank_with_comment(feeder, core, &mut ans.text) {}
 
         if command::eat_inner_script(feeder, core, "do", vec!["done"],  &mut ans.do_script, false) {
-            ans.text.push_str("while");
+            ans.text.push_str(keyword);
             ans.text.push_str(&ans.while_script.as_mut().unwrap().get_text());
             ans.text.push_str("do");
             ans.text.push_str(&ans.do_script.as_mut().unwrap().get_text());
@@ -39,31 +39,6 @@ impl ArithmeticExpr {
         }
     }
 
-    pub fn eval_as_assoc_index(&mut self, core: &mut ShellCore) -> Result<String, String> {
-        let mut ans = String::new();
-
-        for e in &self.elements {
-            match e {
-                ArithElem::Word(w, i) => {
-                    match w.eval_as_value(core) {
-                        Some(s) => {
-                            ans += &s;
-                            if *i > 0 {
-                                ans += "++";
-                            }else if *i < 0 {
-                                ans += "--";
-                            }
-                        },
-                        None => return Err("not an assoc index".to_string()),
-                    }
-                },
-                _ => ans += &e.to_string(),
-            }
-        }
-
-        Ok(ans)
-    }
-
     pub fn eval_as_int(&mut self, core: &mut ShellCore) -> Option<i64> {
         match self.eval_elems(core, true) {
             Ok(ArithElem::Integer(n)) => Some(n),
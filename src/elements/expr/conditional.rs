@@ -4,10 +4,11 @@
 pub mod elem;
 mod parser;
 
-use crate::{utils::error, ShellCore};
-use crate::utils::{file_check, glob};
+use crate::{utils::error, Feeder, ShellCore};
+use crate::utils::{ere, file_check, glob};
+use crate::elements::subscript::Subscript;
 use crate::elements::word::Word;
-use regex::Regex;
+use regex::RegexBuilder;
 use self::elem::CondElem;
 use super::arithmetic::word;
 use super::arithmetic::elem::ArithElem;
@@ -16,7 +17,7 @@ use std::env;
 fn to_operand(w: &Word, core: &mut ShellCore) -> Result<CondElem, String> {
     match w.eval_for_case_pattern(core) {
         Some(v) => Ok(CondElem::Operand(v)),
-        None => return Err(format!("{}: wrong substitution", &w.text)),
+        None => Err(format!("{}: wrong substitution", &w.text)),
     }
 }
 
@@ -25,7 +26,7 @@ fn pop_operand(stack: &mut Vec<CondElem>, core: &mut ShellCore) -> Result<CondEl
         Some(CondElem::InParen(mut expr)) => expr.eval(core),
         Some(CondElem::Word(w)) => to_operand(&w, core),
         Some(elem) => Ok(elem),
-        None => return Err("no operand 1".to_string()),
+        None => Err("no operand 1".to_string()),
     }
 }
 
@@ -152,21 +153,36 @@ impl ConditionalExpr {
             Err(e) => return Err(e + " to conditional unary operator"),
         };
 
-        if op == "-o" || op == "-v" || op == "-z" || op == "-n" {
-            let ans = match op {
-                "-o" => core.options.query(&operand),
-                //"-v" => core.db.get_value(&operand).is_some() || env::var(&operand).is_ok(),
-                "-v" => core.db.has_value(&operand) || env::var(&operand).is_ok(),
-                "-z" => operand.is_empty(),
-                "-n" => operand.len() > 0,
-                _    => false,
-            };
+        let ans = Self::unary_eval(op, &operand, core)?;
+        stack.push( CondElem::Ans(ans) );
+        Ok(())
+    }
 
-            stack.push( CondElem::Ans(ans) );
-            return Ok(());
+    fn is_set(operand: &str, core: &mut ShellCore) -> bool {
+        let (name, sub) = match operand.find('[') {
+            Some(p) if operand.ends_with(']') => operand.split_at(p),
+            _ => return core.db.has_value(operand) || env::var(operand).is_ok(),
+        };
+
+        let mut feeder = Feeder::new(sub);
+        match Subscript::parse(&mut feeder, core) {
+            Some(mut s) if feeder.len() == 0 => match s.eval(core, name) {
+                Ok(index) => core.db.has_array_value(name, &index),
+                Err(_) => false,
+            },
+            _ => false,
         }
+    }
 
-        Self::unary_file_check(op, &operand, stack)
+    pub fn unary_eval(op: &str, operand: &str, core: &mut ShellCore) -> Result<bool, String> {
+        match op {
+            "-o" => Ok(core.options.query(operand)),
+            //"-v" => core.db.get_value(&operand).is_some() || env::var(&operand).is_ok(),
+            "-v" => Ok(Self::is_set(operand, core)),
+            "-z" => Ok(operand.is_empty()),
+            "-n" => Ok(operand.len() > 0),
+            _    => Self::unary_file_check(op, operand),
+        }
     }
 
     fn regex_operation(stack: &mut Vec<CondElem>, core: &mut ShellCore) -> Result<(), String> {
@@ -176,24 +192,45 @@ impl ConditionalExpr {
             Err(e) => return Err(e),
         };
 
-        let left = match pop_operand(stack, core) {
-            Ok(CondElem::Operand(name)) => name,
-            Ok(_)  => return Err("Invalid operand".to_string()),
-            Err(e) => return Err(e),
+        let left = match stack.pop() {
+            Some(CondElem::Word(w)) => match w.tilde_and_dollar_expansion(core) {
+                Ok(mut w) => w.make_unquoted_word().unwrap_or_default(),
+                Err(e)    => return Err(e),
+            },
+            Some(elem) => {
+                stack.push(elem);
+                match pop_operand(stack, core) {
+                    Ok(CondElem::Operand(name)) => name,
+                    Ok(_)  => return Err("Invalid operand".to_string()),
+                    Err(e) => return Err(e),
+                }
+            },
+            None => return Err("no operand".to_string()),
         };
 
-        let right_eval = match right.eval_for_case_pattern(core) {
+        let right_eval = match right.eval_for_regex(core) {
             Some(r) => r,
             None => return Err("Invalid regex".to_string()),
         };
 
-        let re = match Regex::new(&right_eval) {
+        let re = match RegexBuilder::new(&ere::to_rust_regex(&right_eval))
+                       .case_insensitive(core.shopts.query("nocasematch"))
+                       .dot_matches_new_line(true).build() {
             Ok(regex) => regex,
-            Err(e) => return Err(e.to_string()),
+            Err(_) => return Err(format!("{}: invalid regular expression", &right_eval)),
         };
 
-        stack.push( CondElem::Ans(re.is_match(&left)) );
-        return Ok(());
+        let rematch = match re.captures(&left) {
+            Some(caps) => caps.iter()
+                              .map(|m| m.map(|m| m.as_str().to_string()).unwrap_or_default())
+                              .collect(),
+            None => vec![],
+        };
+        let ans = ! rematch.is_empty();
+        core.db.set_array("BASH_REMATCH", rematch, None)?;
+
+        stack.push( CondElem::Ans(ans) );
+        Ok(())
     }
 
     fn bin_operation(op: &str, stack: &mut Vec<CondElem>,
@@ -237,17 +274,7 @@ impl ConditionalExpr {
                 Err(msg) => return Err(msg),
             };
 
-            let ans = match op {
-                "-eq" => lnum == rnum,
-                "-ne" => lnum != rnum,
-                "-lt" => lnum < rnum,
-                "-le" => lnum <= rnum,
-                "-gt" => lnum > rnum,
-                "-ge" => lnum >= rnum,
-                _    => false,
-            };
-
-            stack.push( CondElem::Ans(ans) );
+            stack.push( CondElem::Ans(Self::int_comparison(op, lnum, rnum)) );
             return Ok(());
         }
 
@@ -256,23 +283,32 @@ impl ConditionalExpr {
         Ok(())
     }
 
-    fn unary_file_check(op: &str, s: &String, stack: &mut Vec<CondElem>) -> Result<(), String> {
-        let result = match op {
-            "-a" | "-e"  => file_check::exists(s),
-            "-d"  => file_check::is_dir(s),
-            "-f"  => file_check::is_regular_file(s),
-            "-h" | "-L"  => file_check::is_symlink(s),
-            "-r"  => file_check::is_readable(s),
-            "-t"  => file_check::is_tty(s),
-            "-w"  => file_check::is_writable(s),
-            "-x"  => file_check::is_executable(s),
-            "-b" | "-c" | "-g" | "-k" | "-p" | "-s" | "-u" | "-G" | "-N" | "-O" | "-S"
-                  => file_check::metadata_check(s, op),
-            _  => return Err("unsupported option".to_string()),
-        };
+    pub fn int_comparison(op: &str, lnum: i64, rnum: i64) -> bool {
+        match op {
+            "-eq" => lnum == rnum,
+            "-ne" => lnum != rnum,
+            "-lt" => lnum < rnum,
+            "-le" => lnum <= rnum,
+            "-gt" => lnum > rnum,
+            "-ge" => lnum >= rnum,
+            _    => false,
+        }
+    }
 
-        stack.push( CondElem::Ans(result) );
-        Ok(())
+    fn unary_file_check(op: &str, s: &str) -> Result<bool, String> {
+        match op {
+            "-a" | "-e"  => Ok(file_check::exists(s)),
+            "-d"  => Ok(file_check::is_dir(s)),
+            "-f"  => Ok(file_check::is_regular_file(s)),
+            "-h" | "-L"  => Ok(file_check::is_symlink(s)),
+            "-r"  => Ok(file_check::is_readable(s)),
+            "-t"  => Ok(file_check::is_tty(s)),
+            "-w"  => Ok(file_check::is_writable(s)),
+            "-x"  => Ok(file_check::is_executable(s)),
+            "-b" | "-c" | "-g" | "-k" | "-p" | "-s" | "-u" | "-G" | "-N" | "-O" | "-S"
+                  => Ok(file_check::metadata_check(s, op)),
+            _  => Err("unsupported option".to_string()),
+        }
     }
 
     fn rev_polish_op(elem: &CondElem,
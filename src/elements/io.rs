@@ -21,6 +21,9 @@ pub fn replace(from: RawFd, to: RawFd) -> bool {
     if from < 0 || to < 0 {
         return false;
     }
+    if from == to {
+        return true;
+    }
 
     match unistd::dup2(from, to) {
         Ok(_) => {
@@ -57,8 +60,7 @@ fn share(from: RawFd, to: RawFd) -> bool {
 }
 
 pub fn backup(from: RawFd) -> RawFd {
-    fcntl::fcntl(from, fcntl::F_DUPFD_CLOEXEC(10))
-           .expect("Can't allocate fd for backup")
+    fcntl::fcntl(from, fcntl::F_DUPFD_CLOEXEC(10)).unwrap_or(-1)
 }
 
 pub fn connect(pipe: &mut Pipe, rs: &mut Vec<Redirect>, core: &mut ShellCore) {
@@ -15,6 +15,7 @@ pub struct Pipe {
     pub send: RawFd,
     pub prev: RawFd,
     pub pgid: Pid,
+    pub close_in_child: Vec<RawFd>,
 }
 
 impl Pipe {
@@ -25,6 +26,7 @@ impl Pipe {
             send: -1,
             prev: -1,
             pgid: Pid::from_raw(0),
+            close_in_child: vec![],
         }
     }
 
@@ -57,6 +59,9 @@ impl Pipe {
         io::close(self.recv, "Cannot close in-pipe");
         io::replace(self.send, 1);
         io::replace(self.prev, 0);
+        for fd in &self.close_in_child {
+            let _ = unistd::close(*fd);
+        }
 
         if &self.text == &"|&" {
             io::share(1, 2);
@@ -5,10 +5,13 @@ use std::fs::{File, OpenOptions};
 use std::os::fd::{IntoRawFd, RawFd};
 use std::io::Error;
 use crate::elements::io;
+use crate::elements::substitution::Substitution;
+use crate::elements::subword::Subword;
+use crate::elements::subword::double_quoted::DoubleQuoted;
 use crate::elements::word::Word;
 use crate::{Feeder, ShellCore};
-use crate::utils::{error, exit};
-use nix::unistd;
+use crate::utils::{error, exit, file_check};
+use nix::{fcntl, unistd};
 use nix::unistd::ForkResult;
 use std::os::fd::FromRawFd;
 use std::io::Write;
@@ -22,13 +25,19 @@ pub struct Redirect {
     pub left: String,
     left_fd: RawFd,
     left_backup: RawFd,
-    extra_left_backup: RawFd, // &>, &>>用
+    extra_left_fd: RawFd, // &>, &>>, n>&m-用
+    extra_left_backup: RawFd,
+    heredoc: String,
+    heredoc_quoted: bool,
 }
 
 impl Redirect {
     pub fn connect(&mut self, restore: bool, core: &mut ShellCore) -> bool {
         if self.symbol == "<<<" {
-            return self.redirect_herestring(core);
+            return self.redirect_herestring(restore, core);
+        }
+        if self.symbol == "<<" || self.symbol == "<<-" {
+            return self.redirect_heredoc(restore, core);
         }
 
         let args = match self.right.eval(core) {
@@ -46,124 +55,312 @@ impl Redirect {
 
         self.right.text = args[0].clone();
 
+        if ! restore && self.touches_script_fd(core) {
+            eprintln!("sush: {}: Bad file descriptor", self.text);
+            return false;
+        }
+
         match self.symbol.as_str() {
-            "<" => self.redirect_simple_input(restore),
-            ">" => self.redirect_simple_output(restore),
-            ">&" => self.redirect_output_fd(restore),
-            ">>" => self.redirect_append(restore),
-            "&>" => self.redirect_both_output(restore),
+            "<" => self.redirect_simple_input(restore, core),
+            ">" | ">|" => self.redirect_simple_output(restore, core),
+            ">&" => self.redirect_dup_fd(1, restore, core),
+            "<&" => self.redirect_dup_fd(0, restore, core),
+            ">>" => self.redirect_append(restore, core),
+            "<>" => self.redirect_read_write(restore, core),
+            "&>" | "&>>" => self.redirect_both_output(restore, core),
             _ => exit::internal(" (Unknown redirect symbol)"),
         }
     }
 
+    /* the fd from which the shell reads the script must survive exec */
+    fn touches_script_fd(&self, core: &mut ShellCore) -> bool {
+        let script_fd = match core.script_fd {
+            Some(fd) => fd,
+            None     => return false,
+        };
+        let moved = match self.symbol.as_str() {
+            ">&" | "<&" => self.right.text.strip_suffix("-").and_then(|n| n.parse::<RawFd>().ok()),
+            _ => None,
+        };
+        self.left.parse::<RawFd>().ok() == Some(script_fd) || moved == Some(script_fd)
+    }
+
+    fn is_varname_left(&self) -> bool {
+        self.left.starts_with("{")
+    }
+
     fn set_left_fd(&mut self, default_fd: RawFd) {
         self.left_fd = match self.left.len() {
             0 => default_fd,
+            _ if self.is_varname_left() => -1,
             _ => self.left.parse().unwrap(),
         }
     }
 
-    fn connect_to_file(&mut self, file_open_result: Result<File,Error>, restore: bool) -> bool {
-        if restore {
-            self.left_backup = io::backup(self.left_fd);
-        }
-
+    fn connect_to_file(&mut self, file_open_result: Result<File,Error>,
+                       restore: bool, core: &mut ShellCore) -> bool {
         match file_open_result {
-            Ok(file) => {
-                let fd = file.into_raw_fd();
-                let result = io::replace(fd, self.left_fd);
-                if ! result {
-                    io::close(fd, &format!("sush(fatal): file does not close"));
-                    self.left_fd = -1;
-                }
-                result
-            },
+            Ok(file) => self.connect_fd(file.into_raw_fd(), restore, core),
             _  => {
                 eprintln!("sush: {}: {}", &self.right.text, Error::last_os_error().kind());
+                self.left_fd = -1;
                 false
             },
         }
     }
 
-    fn redirect_simple_input(&mut self, restore: bool) -> bool {
+    fn connect_fd(&mut self, fd: RawFd, restore: bool, core: &mut ShellCore) -> bool {
+        if self.is_varname_left() {
+            let result = self.set_varname_fd(fd, core);
+            io::close(fd, "sush(fatal): file does not close");
+            return result;
+        }
+
+        if restore {
+            self.left_backup = io::backup(self.left_fd);
+        }
+
+        let result = io::replace(fd, self.left_fd);
+        if ! result {
+            io::close(fd, "sush(fatal): file does not close");
+            self.left_fd = -1;
+        }
+        result
+    }
+
+    fn set_varname_fd(&mut self, fd: RawFd, core: &mut ShellCore) -> bool {
+        let name = self.left.trim_start_matches("{").trim_end_matches("}");
+        let new_fd = match fcntl::fcntl(fd, fcntl::F_DUPFD(10)) {
+            Ok(n) => n,
+            Err(e) => {
+                eprintln!("sush: {}: {}", &self.left, e);
+                return false;
+            },
+        };
+
+        if let Err(e) = Substitution::set_target(name, &new_fd.to_string(), core) {
+            error::print(&e, core);
+            io::close(new_fd, &format!("sush(fatal): {}: cannot be closed", new_fd));
+            return false;
+        }
+        true
+    }
+
+    fn redirect_simple_input(&mut self, restore: bool, core: &mut ShellCore) -> bool {
         self.set_left_fd(0);
-        self.connect_to_file(File::open(&self.right.text), restore)
+        self.connect_to_file(File::open(&self.right.text), restore, core)
     }
 
-    fn redirect_simple_output(&mut self, restore: bool) -> bool {
+    fn clobber_check(&mut self, core: &mut ShellCore) -> bool {
+        if self.symbol == ">|" || ! core.db.flags.contains('C')
+        || ! file_check::is_regular_file(&self.right.text) {
+            return true;
+        }
+
+        eprintln!("sush: {}: cannot overwrite existing file", &self.right.text);
+        self.left_fd = -1;
+        false
+    }
+
+    fn redirect_simple_output(&mut self, restore: bool, core: &mut ShellCore) -> bool {
         self.set_left_fd(1);
-        self.connect_to_file(File::create(&self.right.text), restore)
+        if ! self.clobber_check(core) {
+            return false;
+        }
+        self.connect_to_file(File::create(&self.right.text), restore, core)
     }
 
-    fn redirect_output_fd(&mut self, _: bool) -> bool {
-        let fd = match self.right.text.parse::<RawFd>() {
+    fn redirect_read_write(&mut self, restore: bool, core: &mut ShellCore) -> bool {
+        self.set_left_fd(0);
+        self.connect_to_file(OpenOptions::new().create(true)
+                .read(true).write(true).truncate(false).open(&self.right.text), restore, core)
+    }
+
+    fn redirect_dup_fd(&mut self, default_fd: RawFd, restore: bool, core: &mut ShellCore) -> bool {
+        if self.right.text == "-" {
+            return self.redirect_close(default_fd, restore, core);
+        }
+
+        let (num, move_fd) = match self.right.text.strip_suffix("-") {
+            Some(n) => (n.to_string(), true),
+            None    => (self.right.text.clone(), false),
+        };
+
+        let fd = match num.parse::<RawFd>() {
             Ok(n) => n,
-            _     => return false,
+            _ if self.symbol == ">&" && self.left.is_empty() => {
+                return self.redirect_both_output(restore, core);
+            },
+            _ => {
+                eprintln!("sush: {}: ambiguous redirect", &self.right.text);
+                return false;
+            },
         };
 
-        self.set_left_fd(1);
-        io::share(fd, self.left_fd)
+        self.set_left_fd(default_fd);
+        if self.is_varname_left() {
+            if ! self.set_varname_fd(fd, core) {
+                return false;
+            }
+        }else{
+            if fd == self.left_fd {
+                self.left_fd = -1;
+                return true;
+            }
+            if fcntl::fcntl(fd, fcntl::F_GETFD).is_err() {
+                eprintln!("sush: {}: Bad file descriptor", fd);
+                self.left_fd = -1;
+                return false;
+            }
+            if restore {
+                self.left_backup = io::backup(self.left_fd);
+            }
+            if ! io::share(fd, self.left_fd) {
+                self.left_fd = -1;
+                return false;
+            }
+        }
+
+        if move_fd {
+            if restore {
+                self.extra_left_fd = fd;
+                self.extra_left_backup = io::backup(fd);
+            }
+            let _ = unistd::close(fd);
+        }
+        true
+    }
+
+    fn redirect_close(&mut self, default_fd: RawFd, restore: bool, core: &mut ShellCore) -> bool {
+        self.set_left_fd(default_fd);
+        if self.is_varname_left() {
+            let name = self.left.trim_start_matches("{").trim_end_matches("}");
+            let value = match Substitution::parse_target(name, core) {
+                Ok((name, None))      => core.db.get_param(&name).unwrap_or_default(),
+                Ok((name, Some(key))) => core.db.get_array_elem(&name, &key).unwrap_or_default(),
+                Err(_) => String::new(),
+            };
+            return match value.parse::<RawFd>() {
+                Ok(fd) => { let _ = unistd::close(fd); true },
+                _ => {
+                    eprintln!("sush: {}: ambiguous redirect", &self.left);
+                    false
+                },
+            };
+        }
+
+        if restore {
+            self.left_backup = io::backup(self.left_fd);
+        }
+        let _ = unistd::close(self.left_fd);
+        true
     }
 
-    fn redirect_append(&mut self, restore: bool) -> bool {
+    fn redirect_append(&mut self, restore: bool, core: &mut ShellCore) -> bool {
         self.set_left_fd(1);
         self.connect_to_file(OpenOptions::new().create(true)
-                .write(true).append(true).open(&self.right.text), restore)
+                .write(true).append(true).open(&self.right.text), restore, core)
     }
 
-    fn redirect_both_output(&mut self, restore: bool) -> bool {
+    fn redirect_both_output(&mut self, restore: bool, core: &mut ShellCore) -> bool {
         self.left_fd = 1;
-        if ! self.connect_to_file(File::create(&self.right.text), restore){
+        if self.symbol == "&>" && ! self.clobber_check(core) {
+            return false;
+        }
+        let file = match self.symbol.as_str() {
+            "&>>" => OpenOptions::new().create(true).append(true).open(&self.right.text),
+            _ => File::create(&self.right.text),
+        };
+        if ! self.connect_to_file(file, restore, core){
             return false;
         }
 
         if restore {
+            self.extra_left_fd = 2;
             self.extra_left_backup = io::backup(2);
         }
         io::share(1, 2);
         true
     }
 
-    fn redirect_herestring(&mut self, core: &mut ShellCore) -> bool {
+    fn redirect_herestring(&mut self, restore: bool, core: &mut ShellCore) -> bool {
+        let text = self.right.eval_for_case_word(core)
+                       .unwrap_or("".to_string());
+        self.redirect_here_data(&(text + "\n"), restore, core)
+    }
+
+    fn redirect_heredoc(&mut self, restore: bool, core: &mut ShellCore) -> bool {
+        if self.heredoc_quoted {
+            let text = self.heredoc.clone();
+            return self.redirect_here_data(&text, restore, core);
+        }
+
+        let mut feeder = Feeder::new(&remove_line_continuation(&self.heredoc));
+        let mut body = match DoubleQuoted::parse_heredoc(&mut feeder, core) {
+            Some(dq) => dq,
+            None => {
+                error::print("here-document: syntax error", core);
+                return false;
+            },
+        };
+        if let Err(e) = body.substitute(core) {
+            error::print(&e, core);
+            return false;
+        }
+
+        let text = match body.split("") {
+            v if v.is_empty() => body.make_unquoted_string().unwrap_or_default(),
+            v => v.into_iter().map(|mut sw| sw.make_unquoted_string().unwrap_or_default())
+                  .collect::<Vec<String>>().join(" "),
+        };
+        self.redirect_here_data(&text, restore, core)
+    }
+
+    fn redirect_here_data(&mut self, text: &str, restore: bool, core: &mut ShellCore) -> bool {
+        self.set_left_fd(0);
         let (r, s) = unistd::pipe().expect("Cannot open pipe");
         let recv = r.into_raw_fd();
         let send = s.into_raw_fd();
 
-        let text = self.right.eval_for_case_word(core)
-                       .unwrap_or("".to_string());
-
         match unsafe{unistd::fork()} {
             Ok(ForkResult::Child) => {
                 io::close(recv, "herestring close error (child recv)");
                 let mut f = unsafe { File::from_raw_fd(send) };
-                let _ = write!(&mut f, "{}\n", &text);
+                let _ = write!(&mut f, "{}", text);
                 f.flush().unwrap();
                 io::close(send, "herestring close error (child send)");
                 process::exit(0);
             },
             Ok(ForkResult::Parent { child: _ } ) => {
                 io::close(send, "herestring close error (parent send)");
-                io::replace(recv, 0);
+                self.connect_fd(recv, restore, core)
             },
             Err(err) => panic!("sush(fatal): Failed to fork. {}", err),
         }
-
-        true
     }
 
     pub fn restore(&mut self) {
-        if self.left_backup >= 0 && self.left_fd >= 0 {
-            io::replace(self.left_backup, self.left_fd);
+        if self.left_fd >= 0 {
+            match self.left_backup >= 0 {
+                true  => { io::replace(self.left_backup, self.left_fd); },
+                false => { let _ = unistd::close(self.left_fd); },
+            }
         }
-        if self.extra_left_backup >= 0 {
-            io::replace(self.extra_left_backup, 2);
+        if self.extra_left_fd >= 0 && self.extra_left_backup >= 0 {
+            io::replace(self.extra_left_backup, self.extra_left_fd);
         }
+
+        self.left_fd = -1;
+        self.left_backup = -1;
+        self.extra_left_fd = -1;
+        self.extra_left_backup = -1;
     }
 
     pub fn new() -> Redirect {
         Redirect {
             left_fd: -1,
             left_backup: -1,
+            extra_left_fd: -1,
             extra_left_backup: -1,
             ..Default::default()
         }
@@ -195,6 +392,13 @@ impl Redirect {
     }
 
     fn eat_left(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        let len = feeder.scanner_redirect_varname(core);
+        if len > 0 {
+            ans.left = feeder.consume(len);
+            ans.text += &ans.left.clone();
+            return true;
+        }
+
         let len = feeder.scanner_uint(core);
         if len == 0 {
             return true; //左側なし（文法上OK）
@@ -209,6 +413,23 @@ impl Redirect {
         }
     }
 
+    fn eat_heredoc(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) {
+        if ans.symbol != "<<" && ans.symbol != "<<-" {
+            return;
+        }
+
+        let delimiter = ans.right.clone().make_unquoted_word().unwrap_or_default();
+        ans.heredoc_quoted = ans.right.text.contains(['\'', '"', '\\']);
+
+        match feeder.consume_heredoc(&delimiter, ans.symbol == "<<-", core) {
+            Ok(body) => ans.heredoc = body,
+            Err(_) => {
+                core.db.exit_status = 130;
+                feeder.consume(feeder.len());
+            },
+        }
+    }
+
     pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Redirect> {
         let mut ans = Self::new();
         feeder.set_backup(); //追加
@@ -217,6 +438,7 @@ impl Redirect {
            Self::eat_symbol(feeder, &mut ans, core) &&
            Self::eat_right(feeder, &mut ans, core) {
             feeder.pop_backup();
+            Self::eat_heredoc(feeder, &mut ans, core);
             Some(ans)
         }else{
             feeder.rewind(); //追加
@@ -224,3 +446,18 @@ impl Redirect {
         }
     }
 }
+
+fn remove_line_continuation(s: &str) -> String {
+    let mut ans = String::new();
+    let mut esc = false;
+
+    for c in s.chars() {
+        if esc && c == '\n' {
+            ans.pop();
+        }else{
+            ans.push(c);
+        }
+        esc = ! esc && c == '\\';
+    }
+    ans
+}
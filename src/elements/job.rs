@@ -46,6 +46,7 @@ impl Job {
                 let waitstatuses = proc_ctrl::wait_pipeline(core, pids.clone(), exclamation, time);
 
                 Self::check_stop(core, &pipeline.text, &pids, &waitstatuses);
+                core.check_caught_signals();
             }
             do_next = (core.db.exit_status == 0) == (end == "&&");
         }
@@ -84,6 +85,9 @@ impl Job {
             vec![self.exec_fork_bg(core, pgid)]
         };
         eprintln!("{}", &pids[0].unwrap().as_raw());
+        if let Some(pid) = pids.iter().flatten().last() {
+            core.db.last_bg_pid = pid.to_string();
+        }
         let len = pids.len();
         let new_job_id = core.generate_new_job_id();
         core.job_table_priority.insert(0, new_job_id);
@@ -97,6 +101,7 @@ impl Job {
         match unsafe{unistd::fork()} {
             Ok(ForkResult::Child) => {
                 core.initialize_as_subshell(Pid::from_raw(0), pgid);
+                core.jobtable_init_as_subshell(false);
                 self.exec(core, false);
                 exit::normal(core)
             },
@@ -1,7 +1,7 @@
 //SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::{Feeder, ShellCore};
+use crate::{proc_ctrl, Feeder, ShellCore};
 use super::command;
 use super::command::Command;
 use super::Pipe;
@@ -36,6 +36,7 @@ impl Pipeline {
         let mut prev = -1;
         let mut pids = vec![];
         let mut pgid = pgid;
+        let procsub_num = core.process_substitutions.len();
 
         self.set_time(core);
 
@@ -51,6 +52,7 @@ impl Pipeline {
 
         let pid = self.commands[self.pipes.len()].exec(core, &mut Pipe::end(prev, pgid));
         pids.push(pid);
+        proc_ctrl::close_process_substitutions(core, procsub_num);
 
         (pids, self.exclamation, self.time)
     }
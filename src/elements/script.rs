@@ -24,6 +24,9 @@ impl Script {
             if core.word_eval_error {
                 return;
             }
+            if core.db.flags.contains('n') && ! core.db.flags.contains('i') {
+                return;
+            }
             job.exec(core, end == "&");
         }
     }
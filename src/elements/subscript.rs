@@ -3,6 +3,7 @@
 
 use crate::{ShellCore, Feeder};
 use super::expr::arithmetic::ArithmeticExpr;
+use super::word::Word;
 
 #[derive(Debug, Clone, Default)]
 pub struct Subscript {
@@ -22,12 +23,7 @@ impl Subscript {
                 return Err("invalid inner".to_string());
             }
             return match core.db.is_assoc(param_name) {
-                true  => {
-                    match self.inner.as_mut() {
-                        Some(sub) => sub.eval_as_assoc_index(core),
-                        None => Err("no inner".to_string()),
-                    }
-                },
+                true  => Word::eval_without_split(&a.text, core),
                 false => a.eval(core),
             };
         }
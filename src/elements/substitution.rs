@@ -3,8 +3,8 @@
 
 use crate::{ShellCore, Feeder};
 use crate::utils::error;
-use std::env;
 use super::array::Array;
+use super::expr::arithmetic::ArithmeticExpr;
 use super::subscript::Subscript;
 use super::word::Word;
 
@@ -24,11 +24,14 @@ pub struct Substitution {
     value: ParsedDataType,
     evaluated_string: Option<String>,
     evaluated_array: Option<Vec<String>>,
+    evaluated_pairs: Option<Vec<(Option<String>, String)>>,
     append: bool,
 }
 
 impl Substitution {
     pub fn eval(&mut self, core: &mut ShellCore, layer: Option<usize>, env: bool) -> bool {
+
+        let assoc = core.db.is_assoc(&self.name);
         match self.value.clone() {
             ParsedDataType::None 
             => self.evaluated_string = Some("".to_string()),
@@ -36,6 +39,10 @@ impl Substitution {
             => if let Some(e) = self.eval_as_value(&v, core) {
                 self.evaluated_string = Some(e);
             }
+            ParsedDataType::Array(mut a) if assoc || a.has_keys()
+            => if let Ok(pairs) = a.eval_with_keys(core, assoc) {
+                self.evaluated_pairs = Some(pairs);
+            }
             ParsedDataType::Array(mut a) 
             => if let Ok(vec) = self.eval_as_array(&mut a, core) {
                 self.evaluated_array = Some(vec.clone());
@@ -51,7 +58,7 @@ impl Substitution {
                 }
                 return true;
             },
-            true  => self.set_to_env(),
+            true  => self.set_to_env(core),
         }
     }
 
@@ -72,9 +79,8 @@ impl Substitution {
             };
         }
 
-        let index = self.get_index(core)
-                        .unwrap()
-                        .parse::<usize>().map_err(|e| format!("{:?}", e))?;
+        let index = self.get_index(core).unwrap();
+        let index = core.db.get_array_index(&self.name, &index)?;
 
         if let Some(v) = &self.evaluated_string {
             return core.db.set_array_elem(&self.name, &v, index, Some(layer));
@@ -82,6 +88,46 @@ impl Substitution {
         Err("indexed to non array variable".to_string())
     }
  
+    fn set_pairs(&mut self, core: &mut ShellCore, layer: usize) -> Result<(), String> {
+        let pairs = self.evaluated_pairs.clone().unwrap_or_default();
+
+        if core.db.is_assoc(&self.name) {
+            if ! self.append {
+                core.db.set_assoc(&self.name, Some(layer))?;
+            }
+            let mut iter = pairs.into_iter();
+            while let Some((key, value)) = iter.next() {
+                let (key, value) = match key {
+                    Some(k) => (k, value),
+                    None    => (value, iter.next().map(|p| p.1).unwrap_or_default()),
+                };
+                core.db.set_assoc_elem(&self.name, &key, &value, Some(layer))?;
+            }
+            return Ok(());
+        }
+
+        let mut index = match self.append {
+            true  => core.db.get_indexes_all(&self.name).iter()
+                     .filter_map(|i| i.parse::<usize>().ok()).max().map(|n| n + 1).unwrap_or(0),
+            false => {
+                core.db.set_array(&self.name, vec![], Some(layer))?;
+                0
+            },
+        };
+        for (key, value) in pairs {
+            if let Some(k) = key {
+                index = k.parse::<usize>().map_err(|_| format!("{}: bad array subscript", &k))?;
+            }
+            let value = match core.db.has_flag(&self.name, 'i') {
+                true  => Self::eval_as_integer("", &value, core).ok_or(error::syntax(&value))?,
+                false => value,
+            };
+            core.db.set_array_elem(&self.name, &value, index, Some(layer))?;
+            index += 1;
+        }
+        Ok(())
+    }
+
     fn set_param(&mut self, core: &mut ShellCore, layer: usize) -> Result<(), String> {
         let (done, result) = match &self.evaluated_string {
             Some(data) => (true, core.db.set_param(&self.name, &data, Some(layer))),
@@ -102,7 +148,8 @@ impl Substitution {
         let layer = core.db.get_target_layer(&self.name, layer);
 
         if self.evaluated_string.is_none()
-        && self.evaluated_array.is_none() {
+        && self.evaluated_array.is_none()
+        && self.evaluated_pairs.is_none() {
             core.db.exit_status = 1;
             return Err("no value".to_string());
         }
@@ -113,18 +160,25 @@ impl Substitution {
             }
         }
 
-        if core.db.is_assoc(&self.name) {
+        let ans = if self.evaluated_pairs.is_some() {
+            self.set_pairs(core, layer)
+        }else if core.db.is_assoc(&self.name) {
             self.set_assoc(core, layer)
         }else if core.db.is_array(&self.name) {
             self.set_array(core, layer)
         }else {
             self.set_param(core, layer)
+        };
+
+        if ans.is_ok() && core.db.flags.contains('a') {
+            core.db.set_flag(&self.name, 'x');
         }
+        ans
     }
 
-    pub fn set_to_env(&mut self) -> bool {
+    pub fn set_to_env(&mut self, core: &mut ShellCore) -> bool {
         match &self.evaluated_string {
-            Some(v) => env::set_var(&self.name, &v),
+            Some(_) => core.db.set_flag(&self.name, 'x'),
             _ => return false,
         }
         true
@@ -149,18 +203,48 @@ impl Substitution {
         };
 
         match w.eval_as_value(core) {
+            Some(s) if core.db.has_flag(&self.name, 'i') => Self::eval_as_integer(&prev, &s, core),
             Some(s) => Some((prev + &s).to_string()),
             None    => None,
         }
     }
 
+    fn eval_as_integer(prev: &str, s: &str, core: &mut ShellCore) -> Option<String> {
+        let expr = match prev.is_empty() {
+            true  => s.to_string(),
+            false => format!("{}+({})", prev, s),
+        };
+        if expr.trim().is_empty() {
+            return Some("0".to_string());
+        }
+
+        let mut f = Feeder::new(&expr);
+        let result = match ArithmeticExpr::parse(&mut f, core, false) {
+            Some(mut a) if f.len() == 0 => a.eval(core),
+            _ => Err(error::syntax(&expr)),
+        };
+
+        match result {
+            Ok(n) => Some(n),
+            Err(e) => {
+                error::print(&e, core);
+                None
+            },
+        }
+    }
+
     fn eval_as_array(&self, a: &mut Array, core: &mut ShellCore) -> Result<Vec<String>, String> {
         let prev = match self.append {
             true  => core.db.get_array_all(&self.name),
             false => vec![],
         };
 
-        let values = a.eval(core)?;
+        let mut values = a.eval(core)?;
+        if core.db.has_flag(&self.name, 'i') {
+            values = values.iter()
+                     .map(|v| Self::eval_as_integer("", v, core).ok_or(error::syntax(v)))
+                     .collect::<Result<Vec<String>, String>>()?;
+        }
         Ok([prev, values].concat())
         /*
         match a.eval(core) {
@@ -169,6 +253,39 @@ impl Substitution {
         }*/
     }
 
+    /* for builtins that take name or name[subscript] as a target (e.g. printf -v) */
+    pub fn parse_target(target: &str, core: &mut ShellCore) -> Result<(String, Option<String>), String> {
+        let mut feeder = Feeder::new(target);
+        let len = feeder.scanner_name(core);
+        if len == 0 {
+            return Err(format!("`{}': not a valid identifier", target));
+        }
+
+        let name = feeder.consume(len);
+        if feeder.len() == 0 {
+            return Ok((name, None));
+        }
+
+        match Subscript::parse(&mut feeder, core) {
+            Some(mut s) if feeder.len() == 0 => match s.eval(core, &name) {
+                Ok(key) => Ok((name, Some(key))),
+                Err(_)  => Err(format!("{}: bad array subscript", target)),
+            },
+            _ => Err(format!("`{}': not a valid identifier", target)),
+        }
+    }
+
+    pub fn set_target(target: &str, value: &str, core: &mut ShellCore) -> Result<(), String> {
+        match Self::parse_target(target, core)? {
+            (name, None) => core.db.set_param(&name, value, None),
+            (name, Some(key)) if core.db.is_assoc(&name) => core.db.set_assoc_elem(&name, &key, value, None),
+            (name, Some(key)) => {
+                let index = core.db.get_array_index(&name, &key)?;
+                core.db.set_array_elem(&name, value, index, None)
+            },
+        }
+    }
+
     pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Self> {
         let len = feeder.scanner_name(core);
         if len == 0 {
@@ -3,20 +3,24 @@
 
 pub mod simple;
 pub mod single_quoted;
+mod ansi_c_quoted;
 mod braced_param;
 mod command;
+mod process;
 mod escaped_char;
 mod ext_glob;
-mod double_quoted;
+pub mod double_quoted;
 pub mod parameter;
 mod varname;
 mod arithmetic;
 
 use crate::{ShellCore, Feeder};
+use self::ansi_c_quoted::AnsiCQuoted;
 use self::arithmetic::Arithmetic;
 use self::simple::SimpleSubword;
 use self::braced_param::BracedParam;
 use self::command::CommandSubstitution;
+use self::process::ProcessSubstitution;
 use self::escaped_char::EscapedChar;
 use self::ext_glob::ExtGlob;
 use self::double_quoted::DoubleQuoted;
@@ -38,29 +42,97 @@ impl Clone for Box::<dyn Subword> {
     }
 }
 
-fn split_str(s: &str) -> Vec<String> {
-    let mut esc = false;
-    let mut from = 0;
-    let mut pos = 0;
+/* The flag in a field tells whether an empty field must be kept as a word.
+ * Escaped chars (flagged true) are never separators. With max, the last
+ * field takes the rest of the line as read does. */
+pub fn ifs_split(chars: &[(char, bool)], ifs: &str, max: Option<usize>) -> Vec<(String, bool)> {
+    let is_sep = |c: &(char, bool)| ! c.1 && ifs.contains(c.0);
+    let is_space = |c: &(char, bool)| is_sep(c) && " \t\n".contains(c.0);
+    let to_string = |cs: &[(char, bool)]| cs.iter().map(|c| c.0).collect::<String>();
     let mut ans = vec![];
+    let mut fields = 0;
+    let mut pos = 0;
+
+    while pos < chars.len() && is_space(&chars[pos]) {
+        pos += 1;
+    }
+    if pos > 0 {
+        ans.push((String::new(), false));
+    }
 
-    for c in s.chars() {
-        pos += c.len_utf8();
-        if esc || c == '\\' {
-            esc = ! esc;
-            continue;
+    while pos < chars.len() {
+        if max == Some(fields + 1) {
+            ans.push((to_string(rest_of_line(&chars[pos..], is_sep, is_space)), false));
+            return ans;
         }
 
-        if " \t\n".contains(c) {
-            ans.push(s[from..pos-c.len_utf8()].to_string());
-            from = pos;
+        let start = pos;
+        while pos < chars.len() && ! is_sep(&chars[pos]) {
+            pos += 1;
+        }
+        let field = to_string(&chars[start..pos]);
+        if pos == chars.len() {
+            ans.push((field, false));
+            return ans;
+        }
+
+        while pos < chars.len() && is_space(&chars[pos]) {
+            pos += 1;
+        }
+        if pos < chars.len() && is_sep(&chars[pos]) && ! is_space(&chars[pos]) {
+            pos += 1;
+            while pos < chars.len() && is_space(&chars[pos]) {
+                pos += 1;
+            }
         }
+        ans.push((field, true));
+        fields += 1;
     }
 
-    ans.push(s[from..].to_string());
+    ans.push((String::new(), false));
     ans
 }
 
+/* trailing IFS whitespace is removed, and also a trailing separator
+ * if it is the only separator left */
+fn rest_of_line(rest: &[(char, bool)], is_sep: impl Fn(&(char, bool)) -> bool,
+                is_space: impl Fn(&(char, bool)) -> bool) -> &[(char, bool)] {
+    let mut end = rest.len();
+    while end > 0 && is_space(&rest[end-1]) {
+        end -= 1;
+    }
+    if end > 0 && is_sep(&rest[end-1]) {
+        let mut e = end - 1;
+        while e > 0 && is_space(&rest[e-1]) {
+            e -= 1;
+        }
+        if ! rest[..e].iter().any(&is_sep) {
+            end = e;
+        }
+    }
+    &rest[..end]
+}
+
+fn ifs_fields(s: &str, ifs: &str) -> Vec<(String, bool)> {
+    let chars: Vec<(char, bool)> = s.chars().map(|c| (c, false)).collect();
+    ifs_split(&chars, ifs, None)
+}
+
+fn to_subwords(fields: Vec<(String, bool)>) -> Vec<Box<dyn Subword>> {
+    fields.into_iter().map(|(s, keep)| match s.is_empty() && keep {
+        true  => Box::new(SingleQuoted{ text: "''".to_string() }) as Box<dyn Subword>,
+        false => Box::new(SimpleSubword{ text: s }) as Box<dyn Subword>,
+    }).collect()
+}
+
+pub fn split_str(s: &str, ifs: &str) -> Vec<Box<dyn Subword>> {
+    to_subwords(ifs_fields(s, ifs))
+}
+
+pub fn split_array(array: &[String], ifs: &str) -> Vec<Box<dyn Subword>> {
+    to_subwords(array.iter().flat_map(|e| ifs_fields(e, ifs)).collect())
+}
+
 pub trait Subword {
     fn get_text(&self) -> &str;
     fn set_text(&mut self, _: &str) {}
@@ -68,12 +140,11 @@ pub trait Subword {
     fn substitute(&mut self, _: &mut ShellCore) -> Result<(), String> {Ok(())}
     fn get_alternative_subwords(&self) -> Vec<Box<dyn Subword>> {vec![]}
 
-    fn split(&self) -> Vec<Box<dyn Subword>>{
-        let f = |s| Box::new( SimpleSubword {text: s}) as Box<dyn Subword>;
-        split_str(self.get_text()).iter().map(|s| f(s.to_string())).collect()
-    }
+    fn split(&self, _: &str) -> Vec<Box<dyn Subword>>{ vec![] }
+    fn to_expanded(&self) -> Box<dyn Subword> { self.boxed_clone() }
 
     fn make_glob_string(&mut self) -> String {self.get_text().to_string()}
+    fn make_regex(&mut self) -> String {self.get_text().to_string()}
 
     fn make_unquoted_string(&mut self) -> Option<String> {
         match self.get_text() {
@@ -89,40 +160,12 @@ pub trait Subword {
     fn get_child_subwords(&self) -> Vec<Box<dyn Subword>> { vec![] }
 }
 
-fn replace_history_expansion(feeder: &mut Feeder, core: &mut ShellCore) -> bool {
-    let len = feeder.scanner_history_expansion(core);
-    if len == 0 {
-        return false;
-    }
-
-    let history_len = core.history.len();
-    if history_len < 2 {
-        feeder.replace(len, "");
-        return true;
-    }
-
-    let mut his = String::new();
-    for h in &core.history[1..] {
-        let last = h.split(" ").last().unwrap();
-
-        if ! last.starts_with("!$") {
-            his = last.to_string();
-            break;
-        }
-    }
-
-    feeder.replace(len, &his);
-    true
-}
-
 pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Box<dyn Subword>> {
-    if replace_history_expansion(feeder, core) {
-        return parse(feeder, core);
-    }
-
     if let Some(a) = BracedParam::parse(feeder, core){ Some(Box::new(a)) }
     else if let Some(a) = Arithmetic::parse(feeder, core){ Some(Box::new(a)) }
     else if let Some(a) = CommandSubstitution::parse(feeder, core){ Some(Box::new(a)) }
+    else if let Some(a) = ProcessSubstitution::parse(feeder, core){ Some(Box::new(a)) }
+    else if let Some(a) = AnsiCQuoted::parse(feeder, core){ Some(Box::new(a)) }
     else if let Some(a) = SingleQuoted::parse(feeder, core){ Some(Box::new(a)) }
     else if let Some(a) = DoubleQuoted::parse(feeder, core){ Some(Box::new(a)) }
     else if let Some(a) = ExtGlob::parse(feeder, core){ Some(Box::new(a)) }
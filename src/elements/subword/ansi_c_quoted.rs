@@ -0,0 +1,55 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{ShellCore, Feeder};
+use crate::utils::ansi_c;
+use super::Subword;
+
+#[derive(Debug, Clone)]
+pub struct AnsiCQuoted {
+    pub text: String,
+    value: String,
+}
+
+impl Subword for AnsiCQuoted {
+    fn get_text(&self) -> &str {&self.text}
+    fn boxed_clone(&self) -> Box<dyn Subword> {Box::new(self.clone())}
+
+    fn substitute(&mut self, _: &mut ShellCore) -> Result<(), String> {
+        self.text = self.value.clone();
+        Ok(())
+    }
+
+    fn make_unquoted_string(&mut self) -> Option<String> {
+        Some( self.value.clone() )
+    }
+
+    fn make_glob_string(&mut self) -> String {
+        self.value.replace("\\", "\\\\")
+            .replace("*", "\\*")
+            .replace("?", "\\?")
+            .replace("[", "\\[")
+            .replace("]", "\\]")
+    }
+
+    fn make_regex(&mut self) -> String {
+        regex::escape(&self.make_unquoted_string().unwrap_or_default())
+    }
+
+}
+
+impl AnsiCQuoted {
+    pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Self> {
+        match feeder.scanner_ansi_c_quoted_subword(core) {
+            0 => None,
+            n => {
+                let text = feeder.consume(n);
+                let mut value = ansi_c::unescape(&text[2..n-1]);
+                if let Some(pos) = value.find('\0') {
+                    value.truncate(pos);
+                }
+                Some(AnsiCQuoted{ text, value })
+            },
+        }
+    }
+}
@@ -3,6 +3,7 @@
 
 use crate::{ShellCore, Feeder};
 use crate::elements::command::arithmetic::ArithmeticCommand;
+use crate::elements::subword;
 use crate::elements::subword::Subword;
 
 #[derive(Debug, Clone)]
@@ -22,6 +23,10 @@ impl Subword for Arithmetic {
         }
         Err("arithmetic operation failed".to_string())
     }
+
+    fn split(&self, ifs: &str) -> Vec<Box<dyn Subword>>{
+        subword::split_str(&self.text, ifs)
+    }
 }
 
 impl Arithmetic {
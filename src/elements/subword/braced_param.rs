@@ -5,6 +5,8 @@ mod value_check;
 mod substr;
 mod remove;
 mod replace;
+mod case_conv;
+mod transform;
 
 use crate::{ShellCore, Feeder};
 use crate::elements::subword;
@@ -12,9 +14,11 @@ use crate::elements::subword::Subword;
 use crate::elements::subscript::Subscript;
 use crate::elements::word::Word;
 use crate::utils;
+use self::case_conv::CaseConv;
 use self::remove::Remove;
 use self::replace::Replace;
 use self::substr::Substr;
+use self::transform::Transform;
 use self::value_check::ValueCheck;
 use super::simple::SimpleSubword;
 
@@ -28,17 +32,22 @@ struct Param {
 pub struct BracedParam {
     text: String,
     array: Vec<String>,
+    index: String,
 
     param: Param,
     replace: Option<Replace>,
     substr: Option<Substr>,
     remove: Option<Remove>,
     value_check: Option<ValueCheck>,
+    case_conv: Option<CaseConv>,
+    transform: Option<Transform>,
 
     unknown: String,
     is_array: bool,
+    array_result: bool,
     num: bool,
     indirect: bool,
+    prefix_list: bool,
 }
 
 impl Subword for BracedParam {
@@ -50,6 +59,17 @@ impl Subword for BracedParam {
             return Err(format!("{}: bad substitution", &self.text));
         }
 
+        if self.prefix_list {
+            return self.prefix_list_operation(core);
+        }
+
+        if self.indirect && self.is_index_list() {
+            self.array = core.db.get_indexes_all(&self.param.name);
+            self.text = self.join_list(core);
+            self.array_result = true;
+            return Ok(());
+        }
+
         if self.indirect {
             let value = core.db.get_param(&self.param.name).unwrap_or_default();
             if utils::is_param(&value) {
@@ -68,10 +88,29 @@ impl Subword for BracedParam {
 
         if self.param.name == "@" {
             if let Some(s) = self.substr.as_mut() {
+                self.array_result = true;
                 return s.set_partial_position_params(&mut self.array, &mut self.text, core);
             }
         }
 
+        if self.is_whole_array() {
+            self.array = core.db.get_position_params();
+            self.text = self.array.join(" ");
+            self.array_result = true;
+            return self.optional_operation(core);
+        }
+
+        if core.db.flags.contains('u') && self.value_check.is_none()
+        && self.param.name != "@" && self.param.name != "*"
+        && ! core.db.has_scalar_value(&self.param.name) {
+            return Err(format!("{}: unbound variable", &self.param.name));
+        }
+
+        if (self.param.name == "@" || self.param.name == "*") && ! self.num {
+            self.array = core.db.get_position_params();
+            self.array_result = true;
+        }
+
         let value = core.db.get_param(&self.param.name).unwrap_or_default();
         self.text = match self.num {
             true  => value.chars().count().to_string(),
@@ -90,11 +129,18 @@ impl Subword for BracedParam {
 
         let check = self.value_check.clone().unwrap();
         match &check.alternative_value {
-            Some(w) => w.subwords.to_vec(),
+            Some(w) => w.subwords.iter().map(|sw| sw.to_expanded()).collect(),
             None    => vec![],
         }
     }
 
+    fn split(&self, ifs: &str) -> Vec<Box<dyn Subword>>{
+        match self.array_result {
+            true  => subword::split_array(&self.array, ifs),
+            false => subword::split_str(&self.text, ifs),
+        }
+    }
+
     fn is_array(&self) -> bool {self.is_array}
     fn get_array_elem(&self) -> Vec<String> {self.array.clone()}
 }
@@ -113,37 +159,98 @@ impl BracedParam {
         true
     }
 
+    fn is_index_list(&self) -> bool {
+        match &self.param.subscript {
+            Some(s) => s.text == "[@]" || s.text == "[*]",
+            None => false,
+        }
+    }
+
+    /* the * forms are joined with the first character of IFS like "$*" */
+    fn join_list(&self, core: &mut ShellCore) -> String {
+        match self.is_array {
+            true  => self.array.join(" "),
+            false => self.array.join(&core.db.get_ifs_separator()),
+        }
+    }
+
+    fn prefix_list_operation(&mut self, core: &mut ShellCore) -> Result<(), String> {
+        self.array = core.db.get_keys().into_iter()
+                     .filter(|k| k.starts_with(&self.param.name))
+                     .filter(|k| core.db.has_value(k))
+                     .collect();
+        self.text = self.join_list(core);
+        self.array_result = true;
+        Ok(())
+    }
+
+    fn is_whole_array(&self) -> bool {
+        if self.case_conv.is_none() && self.transform.is_none() {
+            return false;
+        }
+
+        match &self.param.subscript {
+            Some(s) => s.text == "[@]" || s.text == "[*]",
+            None => self.param.name == "@" || self.param.name == "*",
+        }
+    }
+
     fn subscript_operation(&mut self, core: &mut ShellCore) -> Result<(), String> {
         let index = self.param.subscript.clone().unwrap().eval(core, &self.param.name)?;
+        self.index = index.clone();
         /*
         let index = match self.param.subscript.clone().unwrap().eval(core, &self.param.name) {
             Some(s) => s,
             None => return Err("index evaluation error".to_string()),
         };*/
 
+        if core.db.flags.contains('u') && self.value_check.is_none()
+        && index != "@" && index != "*"
+        && ! core.db.has_array_value(&self.param.name, &index) {
+            return Err(format!("{}[{}]: unbound variable", &self.param.name, &index));
+        }
+
         if core.db.is_assoc(&self.param.name) {
             return self.subscript_operation_assoc(core, &index);
         }
 
-        if index.as_str() == "@" {
+        if index.as_str() == "@" || index.as_str() == "*" {
             self.array = core.db.get_array_all(&self.param.name);
+            self.array_result = ! self.num;
         }
 
         self.text = match (self.num, index.as_str()) {
             (true, "@") => core.db.len(&self.param.name).to_string(),
-            (true, _)   => core.db.get_array_elem(&self.param.name, &index).unwrap().chars().count().to_string(),
-            (false, _)  => core.db.get_array_elem(&self.param.name, &index).unwrap(),
+            (true, _)   => core.db.get_array_elem(&self.param.name, &index).unwrap_or_default().chars().count().to_string(),
+            (false, _)  => core.db.get_array_elem(&self.param.name, &index).unwrap_or_default(),
        };
        self.optional_operation(core)
     }
 
     fn subscript_operation_assoc(&mut self, core: &mut ShellCore, index: &str) -> Result<(), String> {
+        if index == "@" || index == "*" {
+            self.array = core.db.get_array_all(&self.param.name);
+            self.array_result = ! self.num;
+        }
+
         let s = core.db.get_array_elem(&self.param.name, index)?;
-        self.text = s;
-        Ok(())
+        self.text = match (self.num, index) {
+            (true, "@") | (true, "*") => core.db.len(&self.param.name).to_string(),
+            (true, _)  => s.chars().count().to_string(),
+            (false, _) => s,
+        };
+        match self.case_conv.is_some() || self.transform.is_some() {
+            true  => self.optional_operation(core),
+            false => Ok(()),
+        }
     }
 
     fn optional_operation(&mut self, core: &mut ShellCore) -> Result<(), String> {
+        if self.substr.is_some() || self.value_check.is_some()
+        || self.remove.is_some() || self.replace.is_some() {
+            self.array_result = false;
+        }
+
         self.text = if let Some(s) = self.substr.as_mut() {
             s.get_text(&self.text, core)?
         }else if let Some(v) = self.value_check.as_mut() {
@@ -152,6 +259,29 @@ impl BracedParam {
             r.set(&mut self.text, core)?
         }else if let Some(r) = &self.replace {
             r.get_text(&self.text, core)?
+        }else if let Some(c) = &self.case_conv {
+            match self.is_whole_array() {
+                true  => {
+                    self.array = self.array.iter().map(|e| c.get_text(e, core))
+                                     .collect::<Result<Vec<String>, String>>()?;
+                    self.array.join(" ")
+                },
+                false => c.get_text(&self.text, core)?,
+            }
+        }else if let Some(t) = &self.transform {
+            match self.is_whole_array() {
+                true  => {
+                    self.array = t.get_array(&self.param.name, &self.array, core)?;
+                    self.array.join(" ")
+                },
+                false => {
+                    let index = match self.param.subscript.is_some() {
+                        true  => self.index.clone(),
+                        false => "0".to_string(),
+                    };
+                    t.get_text(&self.param.name, &index, &self.text, core)?
+                },
+            }
         }else{
             self.text.clone()
         };
@@ -215,6 +345,17 @@ impl BracedParam {
         feeder.starts_with("}")
     }
 
+    fn eat_prefix_list(feeder: &mut Feeder, ans: &mut Self) -> bool {
+        if ! feeder.starts_with("*}") && ! feeder.starts_with("@}") {
+            return false;
+        }
+
+        ans.is_array = feeder.starts_with("@");
+        ans.prefix_list = true;
+        ans.text += &feeder.consume(1);
+        true
+    }
+
     fn eat_unknown(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) {
         if feeder.len() == 0 {
             feeder.feed_additional_line(core);
@@ -242,17 +383,24 @@ impl BracedParam {
         if feeder.starts_with("#") && ! feeder.starts_with("#}") {
             ans.num = true;
             ans.text += &feeder.consume(1);
-        }else if feeder.starts_with("!") {
+        }else if feeder.starts_with("!") && ! feeder.starts_with("!}") {
             ans.indirect = true;
             ans.text += &feeder.consume(1);
         }
 
         if Self::eat_param(feeder, &mut ans, core) {
+            if ans.indirect && Self::eat_prefix_list(feeder, &mut ans) {
+                ans.text += &feeder.consume(1);
+                return Some(ans);
+            }
+
             Self::eat_subscript(feeder, &mut ans, core);
             let _ = ValueCheck::eat(feeder, &mut ans, core) 
                  || Substr::eat(feeder, &mut ans, core)
                  || Remove::eat(feeder, &mut ans, core)
-                 || Replace::eat(feeder, &mut ans, core);
+                 || Replace::eat(feeder, &mut ans, core)
+                 || CaseConv::eat(feeder, &mut ans, core)
+                 || Transform::eat(feeder, &mut ans, core);
         }
 
         while ! feeder.starts_with("}") {
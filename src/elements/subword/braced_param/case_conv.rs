@@ -0,0 +1,57 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{Feeder, ShellCore};
+use crate::elements::subword::braced_param::Word;
+use crate::utils::glob;
+use super::BracedParam;
+
+#[derive(Debug, Clone, Default)]
+pub struct CaseConv {
+    pub symbol: String,
+    pub pattern: Option<Word>,
+}
+
+impl CaseConv {
+    fn convert(&self, ch: char) -> String {
+        match self.symbol.starts_with("^") {
+            true  => ch.to_uppercase().to_string(),
+            false => ch.to_lowercase().to_string(),
+        }
+    }
+
+    pub fn get_text(&self, text: &str, core: &mut ShellCore) -> Result<String, String> {
+        let pattern = match &self.pattern {
+            Some(w) if ! w.subwords.is_empty() => w.eval_for_case_word(core)
+                                                   .ok_or("evaluation error")?,
+            _ => "?".to_string(),
+        };
+        let extglob = core.shopts.query("extglob");
+        let all = self.symbol.len() == 2;
+
+        let mut ans = String::new();
+        for (i, ch) in text.chars().enumerate() {
+            if (all || i == 0)
+            && glob::parse_and_compare(&ch.to_string(), &pattern, extglob) {
+                ans += &self.convert(ch);
+            }else{
+                ans.push(ch);
+            }
+        }
+        Ok(ans)
+    }
+
+    pub fn eat(feeder: &mut Feeder, ans: &mut BracedParam, core: &mut ShellCore) -> bool {
+        let len = feeder.scanner_parameter_case_symbol();
+        if len == 0 {
+            return false;
+        }
+
+        let symbol = feeder.consume(len);
+        ans.text += &symbol;
+
+        let pattern = Some(BracedParam::eat_subwords(feeder, ans, vec!["}"], core));
+        ans.case_conv = Some(CaseConv{ symbol, pattern });
+        true
+    }
+}
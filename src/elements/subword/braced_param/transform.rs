@@ -0,0 +1,109 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{Feeder, ShellCore};
+use crate::feeder::terminal;
+use crate::utils::ansi_c;
+use crate::utils::ansi_c::Quote;
+use super::BracedParam;
+
+#[derive(Debug, Clone, Default)]
+pub struct Transform {
+    pub operator: char,
+}
+
+impl Transform {
+    fn upper_first(text: &str) -> String {
+        let mut chars = text.chars();
+        match chars.next() {
+            Some(c) => c.to_uppercase().to_string() + chars.as_str(),
+            None => String::new(),
+        }
+    }
+
+    /* without [@] or [*], an array is shown in the scalar form of the element */
+    fn assignment(name: &str, index: &str, text: &str, core: &mut ShellCore) -> String {
+        let attrs = core.db.get_attributes(name);
+        if (core.db.is_array(name) || core.db.is_assoc(name))
+        && ! core.db.has_array_value(name, index) {
+            return format!("declare -{} {}", attrs, name);
+        }
+
+        match attrs.as_str() {
+            "" => format!("{}={}", name, ansi_c::quote(text, Quote::Single)),
+            attrs => format!("declare -{} {}={}", attrs, name, ansi_c::quote(text, Quote::Single)),
+        }
+    }
+
+    /* only @K quotes the values; @k gives them as separate words */
+    fn key_value_pairs(name: &str, quote: bool, core: &mut ShellCore) -> Vec<String> {
+        let mut ans = vec![];
+        for key in core.db.get_indexes_all(name) {
+            let value = core.db.get_array_elem(name, &key).unwrap_or_default();
+            ans.push(key);
+            ans.push(match quote {
+                true  => ansi_c::quote(&value, Quote::Double),
+                false => value,
+            });
+        }
+        ans
+    }
+
+    pub fn get_text(&self, name: &str, index: &str, text: &str, core: &mut ShellCore) -> Result<String, String> {
+        let set = name == "@" || name == "*" || core.db.has_value(name);
+        let elem_set = match core.db.is_array(name) || core.db.is_assoc(name) {
+            true  => core.db.has_array_value(name, index),
+            false => set,
+        };
+        let ans = match self.operator {
+            'Q' | 'K' | 'k' if ! elem_set => String::new(),
+            'Q' | 'K' | 'k' => ansi_c::quote(text, Quote::Single),
+            'E' => ansi_c::unescape(text),
+            'P' => terminal::expand_prompt(text),
+            'A' if ! set => String::new(),
+            'A' => Self::assignment(name, index, text, core),
+            'a' => core.db.get_attributes(name),
+            'U' => text.to_uppercase(),
+            'u' => Self::upper_first(text),
+            'L' => text.to_lowercase(),
+            c => return Err(format!("{}: bad substitution", c)),
+        };
+        Ok(ans)
+    }
+
+    pub fn get_array(&self, name: &str, array: &[String], core: &mut ShellCore) -> Result<Vec<String>, String> {
+        match self.operator {
+            'A' if name == "@" || name == "*" => {
+                let args = array.iter().map(|a| ansi_c::quote(a, Quote::Single)).collect::<Vec<String>>();
+                Ok(vec![ format!("set -- {}", args.join(" ")) ])
+            },
+            'A' => Ok(vec![ core.db.get_declare_string(name).unwrap_or_default() ]),
+            'K' if name != "@" && name != "*" => Ok(vec![ Self::key_value_pairs(name, true, core).join(" ") ]),
+            'k' if name != "@" && name != "*" => Ok(Self::key_value_pairs(name, false, core)),
+            _ => array.iter().map(|e| self.get_text(name, "0", e, core)).collect(),
+        }
+    }
+
+    pub fn eat(feeder: &mut Feeder, ans: &mut BracedParam, core: &mut ShellCore) -> bool {
+        if ! feeder.starts_with("@") {
+            return false;
+        }
+
+        ans.text += &feeder.consume(1);
+        if feeder.len() == 0 {
+            feeder.feed_additional_line(core);
+        }
+
+        match feeder.nth(0) {
+            Some(c) if "QEPAaKkUuL".contains(c) => {
+                ans.text += &feeder.consume(1);
+                ans.transform = Some(Transform{ operator: c });
+                true
+            },
+            _ => {
+                ans.unknown += "@";
+                false
+            },
+        }
+    }
+}
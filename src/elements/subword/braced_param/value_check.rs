@@ -12,14 +12,17 @@ pub struct ValueCheck {
 }
 
 impl ValueCheck {
-    pub fn set(&mut self, name: &String, text: &String, core: &mut ShellCore) -> Result<String, String> {
+    pub fn set(&mut self, name: &str, text: &str, core: &mut ShellCore) -> Result<String, String> {
         match self.symbol.as_deref() {
             Some(":-")   => {
-                self.set_alter_word(core)?;
-                Ok(text.clone())
+                match text.is_empty() {
+                    true  => {self.set_alter_word(core)?;},
+                    false => self.alternative_value = None,
+                }
+                Ok(text.to_string())
             },
             Some(":?") => self.colon_question(name, text, core),
-            Some(":=") => self.colon_equal(name, core),
+            Some(":=") => self.colon_equal(name, text, core),
             Some("-")  => self.minus(text),
             Some(":+") => self.colon_plus(text, core),
             Some("+")  => self.plus(name, text, core),
@@ -34,39 +37,43 @@ impl ValueCheck {
         Ok(value.clone())
     }
 
-    fn minus(&mut self, text: &String) -> Result<String, String> {
+    fn minus(&mut self, text: &str) -> Result<String, String> {
         self.alternative_value = None;
         self.symbol = None;
-        Ok(text.clone())
+        Ok(text.to_string())
     }
 
-    fn plus(&mut self, name: &String, text: &String, core: &mut ShellCore) -> Result<String, String> {
-        match core.db.has_value(&name) {
+    fn plus(&mut self, name: &str, text: &str, core: &mut ShellCore) -> Result<String, String> {
+        match core.db.has_value(name) {
             true  => {self.set_alter_word(core)?;},
             false => self.alternative_value = None,
         }
-        Ok(text.clone())
+        Ok(text.to_string())
     }
 
-    fn colon_plus(&mut self, text: &String, core: &mut ShellCore) -> Result<String, String> {
+    fn colon_plus(&mut self, text: &str, core: &mut ShellCore) -> Result<String, String> {
         match text.is_empty() {
             true  => self.alternative_value = None,
             false => {self.set_alter_word(core)?;},
         }
-        Ok(text.clone())
+        Ok(text.to_string())
     }
 
-    fn colon_equal(&mut self, name: &String, core: &mut ShellCore) -> Result<String, String> {
+    fn colon_equal(&mut self, name: &str, text: &str, core: &mut ShellCore) -> Result<String, String> {
+        if ! text.is_empty() {
+            self.alternative_value = None;
+            return Ok(text.to_string());
+        }
         let value = self.set_alter_word(core)?;
-        core.db.set_param(&name, &value, None)?;
+        core.db.set_param(name, &value, None)?;
         self.alternative_value = None;
         Ok(value)
     }
 
-    fn colon_question(&mut self, name: &String, text: &String, core: &mut ShellCore) -> Result<String, String> {
-        if core.db.has_value(&name) {
+    fn colon_question(&mut self, name: &str, text: &str, core: &mut ShellCore) -> Result<String, String> {
+        if core.db.has_value(name) {
             self.alternative_value = None;
-            return Ok(text.clone());
+            return Ok(text.to_string());
         }
         let value = self.set_alter_word(core)?;
         let msg = format!("{}: {}", &name, &value);
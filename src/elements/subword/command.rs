@@ -5,6 +5,7 @@ use crate::{proc_ctrl, ShellCore, Feeder};
 use crate::elements::Pipe;
 use crate::elements::command::Command;
 use crate::elements::command::paren::ParenCommand;
+use crate::elements::subword;
 use crate::elements::subword::Subword;
 use nix::unistd;
 use std::{thread, time};
@@ -31,6 +32,10 @@ impl Subword for CommandSubstitution {
         proc_ctrl::wait_pipeline(core, vec![pid], false, false);
         result
     }
+
+    fn split(&self, ifs: &str) -> Vec<Box<dyn Subword>>{
+        subword::split_str(&self.text, ifs)
+    }
 }
 
 impl CommandSubstitution {
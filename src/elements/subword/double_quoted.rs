@@ -5,7 +5,7 @@ use crate::{ShellCore, Feeder};
 use crate::utils::exit;
 use crate::elements::word::{Word, substitution};
 use crate::elements::subword::CommandSubstitution;
-use super::{BracedParam, EscapedChar, SimpleSubword, Parameter, Subword, VarName};
+use super::{Arithmetic, BracedParam, EscapedChar, SimpleSubword, Parameter, Subword, VarName};
 
 #[derive(Debug, Clone, Default)]
 pub struct DoubleQuoted {
@@ -35,6 +35,10 @@ impl Subword for DoubleQuoted {
                         .replace("]", "\\]");
     }
 
+    fn make_regex(&mut self) -> String {
+        regex::escape(&self.make_unquoted_string().unwrap_or_default())
+    }
+
     fn make_unquoted_string(&mut self) -> Option<String> {
         Some(self.subwords.iter_mut()
             .map(|s| s.make_unquoted_string())
@@ -44,7 +48,7 @@ impl Subword for DoubleQuoted {
             .concat() )
     }
 
-    fn split(&self) -> Vec<Box<dyn Subword>>{
+    fn split(&self, _: &str) -> Vec<Box<dyn Subword>>{
         if self.split_points.len() < 1 {
             return vec![];
         }
@@ -112,6 +116,16 @@ impl DoubleQuoted {
         }
     }
 
+    fn eat_arithmetic(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        if let Some(a) = Arithmetic::parse(feeder, core){
+            ans.text += a.get_text();
+            ans.subwords.push(Box::new(a));
+            true
+        }else{
+            false
+        }
+    }
+
     fn eat_command_substitution(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
         if let Some(a) = CommandSubstitution::parse(feeder, core){
             ans.text += a.get_text();
@@ -168,14 +182,19 @@ impl DoubleQuoted {
     }
 
     pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<DoubleQuoted> {
-        if ! feeder.starts_with("\"") {
+        let len = if feeder.starts_with("\"") {
+            1
+        }else if feeder.starts_with("$\"") { // translation of $"..." is not supported
+            2
+        }else{
             return None;
-        }
+        };
         let mut ans = Self::default();
-        ans.text = feeder.consume(1);
+        ans.text = feeder.consume(len);
 
         loop {
             while Self::eat_braced_param(feeder, &mut ans, core)
+               || Self::eat_arithmetic(feeder, &mut ans, core)
                || Self::eat_command_substitution(feeder, &mut ans, core)
                || Self::eat_special_or_positional_param(feeder, &mut ans, core)
                || Self::eat_doller(feeder, &mut ans)
@@ -193,4 +212,30 @@ impl DoubleQuoted {
             }
         }
     }
+
+    pub fn parse_heredoc(feeder: &mut Feeder, core: &mut ShellCore) -> Option<DoubleQuoted> {
+        let mut ans = Self::default();
+
+        while Self::eat_braced_param(feeder, &mut ans, core)
+           || Self::eat_arithmetic(feeder, &mut ans, core)
+           || Self::eat_command_substitution(feeder, &mut ans, core)
+           || Self::eat_special_or_positional_param(feeder, &mut ans, core)
+           || Self::eat_doller(feeder, &mut ans)
+           || Self::eat_escaped_char(feeder, &mut ans, core)
+           || Self::eat_name(feeder, &mut ans, core)
+           || Self::eat_other(feeder, &mut ans, core)
+           || Self::eat_double_quote(feeder, &mut ans) {}
+
+        match feeder.len() {
+            0 => Some(ans),
+            _ => None,
+        }
+    }
+
+    fn eat_double_quote(feeder: &mut Feeder, ans: &mut Self) -> bool {
+        match feeder.starts_with("\"") {
+            true  => Self::set_simple_subword(feeder, ans, 1),
+            false => false,
+        }
+    }
 }
@@ -30,6 +30,10 @@ impl Subword for EscapedChar {
         }
         self.text.clone()
     }
+
+    fn make_regex(&mut self) -> String {
+        regex::escape(&self.make_unquoted_string().unwrap_or_default())
+    }
 }
 
 impl EscapedChar {
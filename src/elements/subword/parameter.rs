@@ -2,11 +2,19 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{ShellCore, Feeder};
+use crate::elements::subword;
 use super::Subword;
 
 #[derive(Debug, Clone)]
 pub struct Parameter {
     pub text: String,
+    array: Option<Vec<String>>,
+}
+
+impl From<&String> for Parameter {
+    fn from(s: &String) -> Self {
+        Self { text: s.to_string(), array: None }
+    }
 }
 
 impl Subword for Parameter {
@@ -17,11 +25,28 @@ impl Subword for Parameter {
         if ! self.text.starts_with("$") {
             return Ok(());
         }
-        let value = core.db.get_param(&self.text[1..]).unwrap_or(String::new());
+        let name = &self.text[1..];
+        if core.db.flags.contains('u') && name != "@" && name != "*"
+        && ! core.db.has_scalar_value(name) {
+            return Err(format!("{}: unbound variable", name));
+        }
+
+        if name == "@" || name == "*" {
+            self.array = Some(core.db.get_position_params());
+        }
+
+        let value = core.db.get_param(name).unwrap_or(String::new());
         self.text = value.to_string();
         Ok(())
     }
 
+    fn split(&self, ifs: &str) -> Vec<Box<dyn Subword>>{
+        match &self.array {
+            Some(a) => subword::split_array(a, ifs),
+            None    => subword::split_str(&self.text, ifs),
+        }
+    }
+
     fn is_array(&self) -> bool {self.text == "$@"}
 }
 
@@ -29,7 +54,7 @@ impl Parameter {
     pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Self> {
         match feeder.scanner_dollar_special_and_positional_param(core) {
             0 => None,
-            n => Some(Self { text: feeder.consume(n) } ),
+            n => Some(Self::from(&feeder.consume(n))),
         }
     }
 }
@@ -0,0 +1,73 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{ShellCore, Feeder};
+use crate::elements::Pipe;
+use crate::elements::command::Command;
+use crate::elements::command::paren::ParenCommand;
+use crate::elements::subword::Subword;
+use crate::utils::exit;
+use nix::unistd;
+use std::os::fd::IntoRawFd;
+
+#[derive(Debug, Clone)]
+pub struct ProcessSubstitution {
+    pub text: String,
+    direction: String,
+    command: ParenCommand,
+}
+
+impl Subword for ProcessSubstitution {
+    fn get_text(&self) -> &str {self.text.as_ref()}
+    fn boxed_clone(&self) -> Box<dyn Subword> {Box::new(self.clone())}
+
+    fn substitute(&mut self, core: &mut ShellCore) -> Result<(), String> {
+        let (r, s) = unistd::pipe().expect("Cannot open pipe");
+        let recv = r.into_raw_fd();
+        let send = s.into_raw_fd();
+
+        let mut pipe = Pipe::new("|".to_string());
+        pipe.pgid = unistd::getpgrp();
+        pipe.close_in_child = core.process_substitutions.iter().map(|p| p.0).collect();
+        let fd = match self.direction.as_str() {
+            "<" => {
+                pipe.recv = recv;
+                pipe.send = send;
+                recv
+            },
+            _ => {
+                pipe.prev = recv;
+                pipe.recv = send; //closed in the child
+                send
+            },
+        };
+
+        let pid = match self.command.exec(core, &mut pipe) {
+            Some(p) => p,
+            None => exit::internal(" (ProcessSubstitution::substitute)"),
+        };
+        core.process_substitutions.push((fd, pid, self.direction == ">"));
+
+        self.text = format!("/dev/fd/{}", fd);
+        Ok(())
+    }
+}
+
+impl ProcessSubstitution {
+    pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Self> {
+        if ! feeder.starts_with("<(") && ! feeder.starts_with(">(") {
+            return None;
+        }
+        feeder.set_backup();
+        let direction = feeder.consume(1);
+
+        if let Some(pc) = ParenCommand::parse(feeder, core, true) {
+            feeder.pop_backup();
+            let text = direction.clone() + &pc.get_text();
+            Some(ProcessSubstitution {text, direction, command: pc} )
+        }else{
+            feeder.rewind();
+            None
+        }
+    }
+}
@@ -25,7 +25,10 @@ impl Subword for SingleQuoted {
             .replace("]", "\\]")
     }
 
-    fn split(&self) -> Vec<Box<dyn Subword>>{ vec![] }
+    fn make_regex(&mut self) -> String {
+        regex::escape(&self.make_unquoted_string().unwrap_or_default())
+    }
+
 }
 
 impl SingleQuoted {
@@ -2,7 +2,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{ShellCore, Feeder};
-use crate::elements::subword::Subword;
+use crate::elements::subword::{Parameter, Subword};
 
 #[derive(Debug, Clone)]
 pub struct VarName {
@@ -14,6 +14,7 @@ impl Subword for VarName {
     fn set_text(&mut self, text: &str) { self.text = text.to_string(); }
     fn boxed_clone(&self) -> Box<dyn Subword> {Box::new(self.clone())}
     fn is_name(&self) -> bool {true}
+    fn to_expanded(&self) -> Box<dyn Subword> {Box::new(Parameter::from(&self.text))}
 }
 
 impl VarName {
@@ -56,7 +56,8 @@ impl Word {
         let mut ws = vec![];
         for w in ws_after_brace_exp {
             let expanded = w.tilde_and_dollar_expansion(core)?;
-            ws.append( &mut expanded.split_and_path_expansion(core) );
+            let ifs = core.db.get_ifs();
+            ws.append( &mut expanded.split_and_path_expansion(&ifs, core) );
         }
 
         Ok( Self::make_args(&mut ws) )
@@ -64,7 +65,7 @@ impl Word {
 
     pub fn eval_as_value(&self, core: &mut ShellCore) -> Option<String> {
         let mut ws = match self.tilde_and_dollar_expansion(core) {
-            Ok(w) => w.split_and_path_expansion(core),
+            Ok(w) => w.split_and_path_expansion("", core),
             Err(e)    => {
                 error::print(&e, core);
                 return None;
@@ -74,6 +75,28 @@ impl Word {
         Some( Self::make_args(&mut ws).join(" ") )
     }
 
+    /* for assoc keys and values in [key]=value: blanks are kept and no glob is applied */
+    pub fn eval_without_split(text: &str, core: &mut ShellCore) -> Result<String, String> {
+        let mut feeder = Feeder::new(text);
+        let mut ans = String::new();
+        while feeder.len() > 0 {
+            let len = feeder.scanner_blank(core);
+            if len > 0 {
+                ans += &feeder.consume(len);
+                continue;
+            }
+
+            match Word::parse(&mut feeder, core, false) {
+                Some(w) => {
+                    let mut w = w.tilde_and_dollar_expansion(core)?;
+                    ans += &w.make_unquoted_word().unwrap_or_default();
+                },
+                None => ans += &feeder.consume(1),
+            }
+        }
+        Ok(ans)
+    }
+
     pub fn eval_for_case_word(&self, core: &mut ShellCore) -> Option<String> {
         match self.tilde_and_dollar_expansion(core) {
             Ok(mut w) => w.make_unquoted_word(),
@@ -94,6 +117,16 @@ impl Word {
         }
     }
 
+    pub fn eval_for_regex(&self, core: &mut ShellCore) -> Option<String> {
+        match self.tilde_and_dollar_expansion(core) {
+            Ok(mut w) => Some(w.subwords.iter_mut().map(|s| s.make_regex()).collect()),
+            Err(e)    => {
+                error::print(&e, core);
+                None
+            },
+        }
+    }
+
     pub fn tilde_and_dollar_expansion(&self, core: &mut ShellCore) -> Result<Word, String> {
         let mut w = self.clone();
         tilde_expansion::eval(&mut w, core);
@@ -101,11 +134,15 @@ impl Word {
         Ok(w)
     }
 
-    pub fn split_and_path_expansion(&self, core: &mut ShellCore) -> Vec<Word> {
+    pub fn split_and_path_expansion(&self, ifs: &str, core: &mut ShellCore) -> Vec<Word> {
         let mut ans = vec![];
         let extglob = core.shopts.query("extglob");
-        for mut w in split::eval(self, core) {
-            ans.append(&mut path_expansion::eval(&mut w, extglob) );
+        let noglob = core.db.flags.contains('f');
+        for mut w in split::eval(self, ifs) {
+            match noglob {
+                true  => ans.push(w),
+                false => ans.append(&mut path_expansion::eval(&mut w, extglob) ),
+            }
         }
         ans
     }
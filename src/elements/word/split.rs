@@ -1,12 +1,11 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::ShellCore;
 use crate::elements::word::Word;
 use crate::elements::subword::Subword;
 
-pub fn eval(word: &Word, core: &mut ShellCore) -> Vec<Word> {
-    let (pos, mut split) = find_pos(word);
+pub fn eval(word: &Word, ifs: &str) -> Vec<Word> {
+    let (pos, mut split) = find_pos(word, ifs);
     if split.is_empty() {
         return vec![word.clone()];
     }
@@ -24,12 +23,12 @@ pub fn eval(word: &Word, core: &mut ShellCore) -> Vec<Word> {
     let mut right = gen_word(word.subwords[pos+1..].to_vec());
     right.subwords.insert(0, split.remove(0));
 
-    [ ans, eval(&right, core) ].concat()
+    [ ans, eval(&right, ifs) ].concat()
 }
 
-pub fn find_pos(word: &Word) -> (usize, Vec<Box<dyn Subword>>) {
+pub fn find_pos(word: &Word, ifs: &str) -> (usize, Vec<Box<dyn Subword>>) {
     for (i, sw) in word.subwords.iter().enumerate() {
-        let split = sw.split();
+        let split = sw.split(ifs);
         if split.len() >= 2 {
             return (i, split);
         }
@@ -51,7 +51,7 @@ fn connect_names(subwords: &mut [Box<dyn Subword>]) {
     }
 
     if pos > 1 {
-        subwords[0] = Box::new(Parameter{ text: text });
+        subwords[0] = Box::new(Parameter::from(&text));
         subwords[1..pos].iter_mut().for_each(|s| s.set_text(""));
     }
 }
@@ -1,20 +1,25 @@
 //SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
-mod terminal;
+pub mod terminal;
+pub mod history_expansion;
 mod scanner;
 
 use std::{io, process};
 use std::fs::File;
 use std::io::{BufRead, BufReader, Lines};
+use std::os::fd::{AsRawFd, FromRawFd, RawFd};
+use nix::fcntl;
 use crate::ShellCore;
-use crate::utils::exit;
+use crate::utils::{error, exit};
+use self::history_expansion::{Expanded, HistoryMemo};
 use std::sync::atomic::Ordering::Relaxed;
 
 #[derive(Debug)]
 pub enum InputError {
     Interrupt,
     Eof,
+    Canceled,
 }
 
 #[derive(Debug, Default)]
@@ -23,7 +28,10 @@ pub struct Feeder {
     backup: Vec<String>,
     pub nest: Vec<(String, Vec<String>)>,
     pub lineno: usize,
+    heredoc_lines: usize,
     script_lines: Option<Lines<BufReader<File>>>,
+    history_memo: HistoryMemo,
+    reedit_line: String,
 }
 
 impl Feeder {
@@ -36,16 +44,28 @@ impl Feeder {
         }
     }
 
-    pub fn set_file(&mut self, s: &str) {
+    /* The script is moved to a high fd as bash does with 255
+     * so that redirections in the script don't overwrite it. */
+    pub fn set_file(&mut self, s: &str) -> RawFd {
         let file = File::open(s).unwrap();
+        let fd = fcntl::fcntl(file.as_raw_fd(), fcntl::F_DUPFD_CLOEXEC(255))
+                 .expect("sush(fatal): Can't allocate fd for the script");
+        drop(file);
+        let file = unsafe{File::from_raw_fd(fd)};
         self.script_lines = Some(BufReader::new(file).lines());
+        fd
     }
 
     pub fn consume(&mut self, cutpos: usize) -> String {
         let cut = self.remaining[0..cutpos].to_string();
         self.remaining = self.remaining[cutpos..].to_string();
 
-        self.lineno += cut.chars().filter(|c| *c == '\n').count();
+        let newlines = cut.chars().filter(|c| *c == '\n').count();
+        self.lineno += newlines;
+        if newlines > 0 {
+            self.lineno += self.heredoc_lines;
+            self.heredoc_lines = 0;
+        }
 
         cut
     }
@@ -105,7 +125,7 @@ impl Feeder {
         }
 
         let line = match ! core.read_stdin && self.script_lines.is_none() {
-            true  => terminal::read_line(core, "PS2"),
+            true  => self.read_line_interactive(core, "PS2"),
             false => self.read_script(core),
         };
 
@@ -131,18 +151,103 @@ impl Feeder {
                 core.db.exit_status = 130;
                 false
             },
+            Err(InputError::Canceled) => false,
         }
     }
 
+    pub fn consume_heredoc(&mut self, delimiter: &str, remove_tab: bool,
+                           core: &mut ShellCore) -> Result<String, InputError> {
+        let lineno = self.lineno;
+        let start = loop {
+            if let Some(n) = self.remaining.find('\n') {
+                break n + 1;
+            }
+            match self.feed_additional_line_core(core) {
+                Err(InputError::Eof) => break self.remaining.len(),
+                other => other?,
+            }
+        };
+
+        let mut body = String::new();
+        let mut end = start;
+        loop {
+            let (line, eof) = match self.remaining[end..].find('\n') {
+                Some(n) => (self.remaining[end..end+n+1].to_string(), false),
+                None => {
+                    let rest = &self.remaining[end..];
+                    let rest = if remove_tab { rest.trim_start_matches('\t') }else{ rest };
+                    if rest != delimiter {
+                        match self.feed_additional_line_core(core) {
+                            Ok(()) => continue,
+                            Err(InputError::Eof) => {},
+                            Err(e) => return Err(e),
+                        }
+                    }
+                    (self.remaining[end..].to_string(), true)
+                },
+            };
+            end += line.len();
+
+            let line = if remove_tab { line.trim_start_matches('\t') }else{ &line };
+            if line.trim_end_matches('\n') == delimiter {
+                break;
+            }
+            if eof {
+                body += line;
+                if ! line.is_empty() {
+                    body += "\n";
+                }
+                eprintln!("sush: warning: here-document at line {} delimited by end-of-file (wanted `{}')",
+                          lineno, delimiter);
+                break;
+            }
+            body += line;
+        }
+
+        self.heredoc_lines += self.remaining[start..end].chars().filter(|c| *c == '\n').count();
+        self.remaining.replace_range(start..end, "");
+        Ok(body)
+    }
+
     pub fn feed_line(&mut self, core: &mut ShellCore) -> Result<(), InputError> {
         let line = match ! core.read_stdin && self.script_lines.is_none() {
-            true  => terminal::read_line(core, "PS1"),
+            true  => self.read_line_interactive(core, "PS1"),
             false => self.read_script(core),
         };
 
         line.map(|ln| self.add_line(ln, core))
     }
 
+    fn read_line_interactive(&mut self, core: &mut ShellCore, prompt: &str) -> Result<String, InputError> {
+        let preload = std::mem::take(&mut self.reedit_line);
+        let line = terminal::read_line(core, prompt, &preload)?;
+
+        let expanded = match history_expansion::expand(&line, &mut self.history_memo, core) {
+            Ok(Expanded::Unchanged) => return Ok(line),
+            Ok(Expanded::Changed(s)) => s,
+            Ok(Expanded::PrintOnly(s)) => {
+                eprint!("{}", &s);
+                core.add_history(s.trim_end());
+                return Err(InputError::Canceled);
+            },
+            Err(e) => {
+                error::print(&e, core);
+                if core.shopts.query("histreedit") {
+                    self.reedit_line = line.trim_end_matches('\n').to_string();
+                }
+                return Err(InputError::Canceled);
+            },
+        };
+
+        if core.shopts.query("histverify") {
+            self.reedit_line = expanded.trim_end_matches('\n').to_string();
+            return Err(InputError::Canceled);
+        }
+
+        eprint!("{}", &expanded);
+        Ok(expanded)
+    }
+
     pub fn add_line(&mut self, line: String, core: &mut ShellCore) {
         if core.db.flags.contains('v') {
             eprint!("{}", &line);
@@ -0,0 +1,457 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::ShellCore;
+use crate::utils::ansi_c;
+use crate::utils::ansi_c::Quote;
+
+#[derive(Debug, Default)]
+pub struct HistoryMemo {
+    search: String,
+    subst: Option<(String, String)>,
+}
+
+struct Expansion<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    entries: &'a [String],
+    base: usize,
+    memo: &'a mut HistoryMemo,
+    ans: String,
+    print_only: bool,
+}
+
+pub enum Expanded {
+    Unchanged,
+    Changed(String),
+    PrintOnly(String),
+}
+
+fn entries(core: &mut ShellCore) -> Vec<String> {
+    core.history.iter().rev().map(|h| h.text.replace("↵ \0", "\n")).collect()
+}
+
+fn tokenize(line: &str) -> Vec<String> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut ans = vec![];
+    let mut word = String::new();
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        let c = chars[pos];
+        if c.is_whitespace() || "|&;<>()".contains(c) {
+            if ! word.is_empty() {
+                ans.push(std::mem::take(&mut word));
+            }
+            if ! c.is_whitespace() {
+                let len = chars[pos..].iter().take_while(|d| **d == c && "|&;<>".contains(c)).count();
+                ans.push(chars[pos..pos+std::cmp::max(len, 1)].iter().collect());
+                pos += std::cmp::max(len, 1);
+                continue;
+            }
+        }else if c == '\\' {
+            word.extend(chars[pos..].iter().take(2));
+            pos += 2;
+            continue;
+        }else if c == '\'' || c == '"' {
+            let len = chars[pos+1..].iter().position(|d| *d == c)
+                      .map(|n| n + 2).unwrap_or(chars.len() - pos);
+            word.extend(chars[pos..pos+len].iter());
+            pos += len;
+            continue;
+        }else{
+            word.push(c);
+        }
+        pos += 1;
+    }
+
+    if ! word.is_empty() {
+        ans.push(word);
+    }
+    ans
+}
+
+impl Expansion<'_> {
+    fn next_is(&self, offset: usize, cands: &str) -> bool {
+        match self.chars.get(self.pos + offset) {
+            Some(c) => cands.contains(*c),
+            None    => false,
+        }
+    }
+
+    fn is_inhibited(&self, in_double: bool) -> bool {
+        let prev = |n: usize| match self.pos >= n {
+            true  => self.chars.get(self.pos - n).copied(),
+            false => None,
+        };
+
+        match self.chars.get(self.pos + 1) {
+            None => return true,
+            Some(c) if c.is_whitespace() || *c == '=' || *c == '(' => return true,
+            Some('"') if in_double => return true,
+            _ => {},
+        }
+
+        prev(1) == Some('$') || prev(1) == Some('[')
+        || (prev(1) == Some('{') && prev(2) == Some('$'))
+    }
+
+    fn read_until(&mut self, ends: &str) -> String {
+        let mut ans = String::new();
+        while let Some(c) = self.chars.get(self.pos) {
+            if c.is_whitespace() || ends.contains(*c) {
+                break;
+            }
+            ans.push(*c);
+            self.pos += 1;
+        }
+        ans
+    }
+
+    fn read_number(&mut self) -> Option<usize> {
+        let num: String = self.chars[self.pos..].iter()
+                          .take_while(|c| c.is_ascii_digit()).collect();
+        self.pos += num.len();
+        num.parse::<usize>().ok()
+    }
+
+    fn event(&mut self, expc: char) -> Option<String> {
+        let last = self.entries.last().cloned();
+        let c = self.chars[self.pos];
+
+        if c == expc {
+            self.pos += 1;
+            return last;
+        }
+
+        match c {
+            '#' => {
+                self.pos += 1;
+                Some(self.ans.clone())
+            },
+            '^' | '$' | '*' | '%' | ':' => last,
+            '-' if self.next_is(1, "0123456789") => {
+                self.pos += 1;
+                let n = self.read_number()?;
+                match n > 0 && n <= self.entries.len() {
+                    true  => Some(self.entries[self.entries.len() - n].clone()),
+                    false => None,
+                }
+            },
+            '0'..='9' => {
+                let n = self.read_number()?;
+                self.entries.get(n.checked_sub(self.base + 1)?).cloned()
+            },
+            '?' => {
+                self.pos += 1;
+                let mut s = String::new();
+                while let Some(c) = self.chars.get(self.pos) {
+                    self.pos += 1;
+                    match c {
+                        '?'  => break,
+                        '\n' => { self.pos -= 1; break },
+                        _    => s.push(*c),
+                    }
+                }
+                self.memo.search = s.clone();
+                self.entries.iter().rev().find(|e| e.contains(&s)).cloned()
+            },
+            _ => {
+                let s = self.read_until(":;&|<>()\"'");
+                self.entries.iter().rev().find(|e| e.starts_with(&s)).cloned()
+            },
+        }
+    }
+
+    fn word_index(&mut self, words: &[String]) -> Option<usize> {
+        let c = self.chars.get(self.pos).copied()?;
+        match c {
+            '^' => { self.pos += 1; Some(1) },
+            '$' => { self.pos += 1; words.len().checked_sub(1) },
+            '%' => {
+                self.pos += 1;
+                words.iter().position(|w| ! self.memo.search.is_empty()
+                                          && w.contains(&self.memo.search))
+            },
+            _ => self.read_number(),
+        }
+    }
+
+    fn designator(&mut self, words: &[String]) -> Option<String> {
+        let last = words.len().checked_sub(1)?;
+
+        let (first, end) = if self.next_is(0, "*") {
+            self.pos += 1;
+            (1, last)
+        }else if self.next_is(0, "-") {
+            self.pos += 1;
+            (0, self.word_index(words)?)
+        }else{
+            let first = self.word_index(words)?;
+            if self.next_is(0, "*") {
+                self.pos += 1;
+                (first, last)
+            }else if self.next_is(0, "-") {
+                self.pos += 1;
+                match self.next_is(0, "^$%0123456789") {
+                    true  => (first, self.word_index(words)?),
+                    false => (first, last.checked_sub(1)?),
+                }
+            }else{
+                (first, first)
+            }
+        };
+
+        if end > last || first > end + 1 {
+            return None;
+        }
+        Some(words[first..end+1].join(" "))
+    }
+
+    fn read_subst_part(&mut self, delim: char) -> String {
+        let mut ans = String::new();
+        while let Some(c) = self.chars.get(self.pos).copied() {
+            if c == delim || c == '\n' {
+                break;
+            }
+            if c == '\\' && self.chars.get(self.pos+1) == Some(&delim) {
+                self.pos += 1;
+            }
+            ans.push(self.chars[self.pos]);
+            self.pos += 1;
+        }
+
+        if self.chars.get(self.pos) == Some(&delim) {
+            self.pos += 1;
+        }
+        ans
+    }
+
+    fn read_subst(&mut self) -> Option<(String, String)> {
+        let delim = self.chars.get(self.pos).copied()?;
+        self.pos += 1;
+        let mut old = self.read_subst_part(delim);
+        let new = self.read_subst_part(delim);
+
+        if old.is_empty() {
+            old = match &self.memo.subst {
+                Some((o, _)) => o.clone(),
+                None => self.memo.search.clone(),
+            };
+        }
+        if old.is_empty() {
+            return None;
+        }
+        self.memo.subst = Some((old.clone(), new.clone()));
+        Some((old, new))
+    }
+
+    fn substitute(text: &str, old: &str, new: &str, global: bool) -> Option<String> {
+        if ! text.contains(old) {
+            return None;
+        }
+
+        let mut rep = String::new();
+        let mut chars = new.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (c, chars.peek()) {
+                ('\\', Some('&')) => { rep.push('&'); chars.next(); },
+                ('&', _) => rep += old,
+                _ => rep.push(c),
+            }
+        }
+
+        match global {
+            true  => Some(text.replace(old, &rep)),
+            false => Some(text.replacen(old, &rep, 1)),
+        }
+    }
+
+    fn modify_subst(&mut self, text: &str, global: bool, each_word: bool,
+                    repeat: bool) -> Result<String, ()> {
+        let (old, new) = match repeat {
+            true  => self.memo.subst.clone().ok_or(())?,
+            false => self.read_subst().ok_or(())?,
+        };
+
+        if ! each_word {
+            return Self::substitute(text, &old, &new, global).ok_or(());
+        }
+
+        let words: Vec<String> = tokenize(text).iter()
+            .map(|w| Self::substitute(w, &old, &new, global).unwrap_or(w.to_string()))
+            .collect();
+        match words.join(" ") == text {
+            true  => Err(()),
+            false => Ok(words.join(" ")),
+        }
+    }
+
+    fn modifiers(&mut self, mut text: String) -> Result<String, String> {
+        while self.next_is(0, ":") {
+            let start = self.pos;
+            self.pos += 1;
+            let m = match self.chars.get(self.pos) {
+                Some(c) => *c,
+                None    => return Err(": unrecognized history modifier".to_string()),
+            };
+            self.pos += 1;
+
+            let last_slash = text.rfind('/');
+            let last_dot = text.rfind('.').filter(|d| last_slash.is_none_or(|s| s < *d));
+            let result = match m {
+                'h' => { if let Some(n) = last_slash { text.truncate(n) }; Ok(text.clone()) },
+                't' => Ok(last_slash.map_or(text.clone(), |n| text[n+1..].to_string())),
+                'r' => Ok(last_dot.map_or(text.clone(), |n| text[..n].to_string())),
+                'e' => Ok(last_dot.map_or(text.clone(), |n| text[n..].to_string())),
+                'p' => { self.print_only = true; Ok(text.clone()) },
+                'q' => Ok(ansi_c::quote(&text, Quote::Single)),
+                'x' => Ok(text.split_whitespace().map(|w| ansi_c::quote(w, Quote::Single)).collect::<Vec<String>>().join(" ")),
+                's' => self.modify_subst(&text, false, false, false),
+                '&' => self.modify_subst(&text, false, false, true),
+                'g' | 'a' | 'G' => {
+                    let each = m == 'G';
+                    match self.chars.get(self.pos) {
+                        Some('s') => { self.pos += 1; self.modify_subst(&text, true, each, false) },
+                        Some('&') => { self.pos += 1; self.modify_subst(&text, true, each, true) },
+                        _ => return Err(format!("{}: unrecognized history modifier", m)),
+                    }
+                },
+                _ => return Err(format!("{}: unrecognized history modifier", m)),
+            };
+
+            text = match result {
+                Ok(t) => t,
+                Err(()) => {
+                    let s: String = self.chars[start..self.pos].iter().collect();
+                    return Err(format!("{}: substitution failed", s));
+                },
+            };
+        }
+        Ok(text)
+    }
+
+    fn history_expansion(&mut self, expc: char) -> Result<(), String> {
+        let start = self.pos;
+        self.pos += 1;
+
+        let line = match self.event(expc) {
+            Some(line) => line,
+            None => {
+                let s: String = self.chars[start..self.pos].iter().collect();
+                return Err(format!("{}: event not found", s));
+            },
+        };
+        let designated = self.next_is(0, "^$*%")
+                         || (self.next_is(0, ":") && self.next_is(1, "0123456789^$*-%"));
+        let text = match designated {
+            true => {
+                let d_start = self.pos;
+                if self.next_is(0, ":") {
+                    self.pos += 1;
+                }
+                match self.designator(&tokenize(&line)) {
+                    Some(t) => t,
+                    None => {
+                        let s: String = self.chars[d_start..self.pos].iter().collect();
+                        return Err(format!("{}: bad word specifier", s));
+                    },
+                }
+            },
+            false => line,
+        };
+
+        let text = self.modifiers(text)?;
+        self.ans += &text;
+        Ok(())
+    }
+
+    fn quick_substitution(&mut self) -> Result<(), String> {
+        let line = match self.entries.last() {
+            Some(line) => line.clone(),
+            None => return Err(format!("{}: event not found", self.chars[0])),
+        };
+
+        let subst = match self.modify_subst(&line, false, false, false) {
+            Ok(s) => s,
+            Err(()) => {
+                let s: String = self.chars[..self.pos].iter().collect();
+                return Err(format!("{}: substitution failed", s.trim_end()));
+            },
+        };
+        let text = self.modifiers(subst)?;
+        self.ans += &text;
+        Ok(())
+    }
+
+    fn expand(&mut self, histchars: &[char]) -> Result<bool, String> {
+        let expc = histchars.first().copied();
+        let comc = histchars.get(2).copied();
+        let mut changed = false;
+
+        if let Some(subc) = histchars.get(1) {
+            if self.chars.first() == Some(subc) {
+                self.quick_substitution()?;
+                changed = true;
+            }
+        }
+
+        let (mut in_single, mut in_double) = (false, false);
+        while self.pos < self.chars.len() {
+            let c = self.chars[self.pos];
+            let prev_blank = self.pos == 0 || self.chars[self.pos-1].is_whitespace();
+
+            if in_single {
+                in_single = c != '\'';
+            }else if c == '\\' {
+                self.ans.extend(self.chars[self.pos..].iter().take(2));
+                self.pos += 2;
+                continue;
+            }else if c == '\'' && ! in_double {
+                in_single = true;
+            }else if c == '"' {
+                in_double = ! in_double;
+            }else if Some(c) == comc && prev_blank && ! in_double {
+                self.ans.extend(self.chars[self.pos..].iter());
+                break;
+            }else if Some(c) == expc && ! self.is_inhibited(in_double) {
+                self.history_expansion(c)?;
+                changed = true;
+                continue;
+            }
+
+            self.ans.push(c);
+            self.pos += 1;
+        }
+
+        Ok(changed)
+    }
+}
+
+pub fn expand(line: &str, memo: &mut HistoryMemo, core: &mut ShellCore) -> Result<Expanded, String> {
+    let histchars: Vec<char> = match core.db.has_value("histchars") {
+        true  => core.db.get_param("histchars").unwrap_or_default().chars().collect(),
+        false => "!^#".chars().collect(),
+    };
+
+    if ! histchars.iter().any(|c| line.contains(*c)) {
+        return Ok(Expanded::Unchanged);
+    }
+
+    let entries = entries(core);
+    let mut exp = Expansion {
+        chars: line.chars().collect(),
+        pos: 0,
+        entries: &entries,
+        base: core.history_base,
+        memo,
+        ans: String::new(),
+        print_only: false,
+    };
+
+    match exp.expand(&histchars)? {
+        false => Ok(Expanded::Unchanged),
+        true if exp.print_only => Ok(Expanded::PrintOnly(exp.ans)),
+        true  => Ok(Expanded::Changed(exp.ans)),
+    }
+}
@@ -113,13 +113,6 @@ impl Feeder {
         }
     }
 
-    pub fn scanner_history_expansion(&mut self, _: &mut ShellCore) -> usize {
-        match self.starts_with("!$") {
-            true  => 2,
-            false => 0,
-        }
-    }
-
     pub fn scanner_dollar_special_and_positional_param(&mut self, core: &mut ShellCore) -> usize {
         if ! self.starts_with("$") {
             return 0;
@@ -182,6 +175,29 @@ impl Feeder {
         }
     }
 
+    pub fn scanner_ansi_c_quoted_subword(&mut self, core: &mut ShellCore) -> usize {
+        if ! self.starts_with("$'") {
+            return 0;
+        }
+
+        loop {
+            let mut escaped = false;
+            for (i, ch) in self.remaining.char_indices().skip(2) {
+                if escaped {
+                    escaped = false;
+                }else if ch == '\\' {
+                    escaped = true;
+                }else if ch == '\'' {
+                    return i + 1;
+                }
+            }
+
+            if ! self.feed_additional_line(core) {
+                return 0;
+            }
+        }
+    }
+
     pub fn scanner_inner_subscript(&mut self, core: &mut ShellCore) -> usize {
         let judge = |ch| "]".find(ch) == None;
         self.scanner_chars(judge, core, 0)
@@ -278,7 +294,39 @@ impl Feeder {
 
     pub fn scanner_redirect_symbol(&mut self, core: &mut ShellCore) -> usize {
         self.backslash_check_and_feed(vec!["<<", ">", "&", "<"], core);
-        self.scanner_one_of(&["<<<", "&>", ">&", ">>", "<", ">"])
+        self.scanner_one_of(&["<<<", "<<-", "<<", "&>>", "&>", ">&", "<&",
+                              "<>", ">|", ">>", "<", ">"])
+    }
+
+    pub fn scanner_redirect_varname(&mut self, core: &mut ShellCore) -> usize {
+        if ! self.starts_with("{") {
+            return 0;
+        }
+
+        let c = self.remaining.chars().nth(1).unwrap_or('0');
+        if c.is_ascii_digit() {
+            return 0;
+        }
+
+        let judge = |ch: char| ch == '_' || ch.is_ascii_alphanumeric();
+        let mut len = self.scanner_chars(judge, core, 1);
+        if len == 0 {
+            return 0;
+        }
+        if self.remaining[len+1..].starts_with("[") {
+            match self.remaining[len+1..].find(']') {
+                Some(n) => len += n + 1,
+                None    => return 0,
+            }
+        }
+        if ! self.remaining[len+1..].starts_with("}") {
+            return 0;
+        }
+
+        match self.remaining[len+2..].starts_with(['<', '>']) {
+            true  => len + 2,
+            false => 0,
+        }
     }
 
     pub fn scanner_parameter_alternative_symbol(&mut self) -> usize {
@@ -289,6 +337,10 @@ impl Feeder {
         self.scanner_one_of(&["##", "#", "%%", "%"])
     }
 
+    pub fn scanner_parameter_case_symbol(&mut self) -> usize {
+        self.scanner_one_of(&["^^", "^", ",,", ","])
+    }
+
     pub fn scanner_test_check_option(&mut self, core: &mut ShellCore) -> usize {
         match self.remaining.chars().nth(0) {
             Some('-') => {},
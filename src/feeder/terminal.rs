@@ -4,6 +4,7 @@
 mod completion;
 
 use crate::{file_check, InputError, ShellCore};
+use crate::core::history::HistoryEntry;
 use crate::utils::file;
 use std::io;
 use std::fs::File;
@@ -77,9 +78,8 @@ fn oct_to_hex_in_str(from: &str) -> String {
 }
 
 impl Terminal {
-    pub fn new(core: &mut ShellCore, ps: &str) -> Self {
-        let raw_prompt = core.db.get_param(ps).unwrap_or(String::new());
-        let ansi_on_prompt = oct_to_hex_in_str(&raw_prompt);
+    pub fn new(raw_prompt: &str) -> Self {
+        let ansi_on_prompt = oct_to_hex_in_str(raw_prompt);
 
         let replaced_prompt = Self::make_prompt_string(&ansi_on_prompt);
         let prompt = replaced_prompt.replace("\\[", "").replace("\\]", "").to_string();
@@ -324,7 +324,7 @@ impl Terminal {
     pub fn call_history(&mut self, inc: i32, core: &mut ShellCore){
         let prev = self.hist_ptr;
         let prev_str = self.get_string(self.prompt.chars().count());
-        Self::shift_in_range(&mut self.hist_ptr, inc, 0, std::isize::MAX as usize);
+        Self::shift_in_range(&mut self.hist_ptr, inc, 0, core.history.len() - 1);
 
         self.chars = self.prompt.chars().collect();
         self.chars.extend(core.fetch_history(self.hist_ptr, prev, prev_str).replace("↵ \0", "\n").chars());
@@ -373,10 +373,32 @@ fn on_arrow_key(term: &mut Terminal, core: &mut ShellCore, key: &event::Key, tab
     }
 }
 
-pub fn read_line(core: &mut ShellCore, prompt: &str) -> Result<String, InputError>{
-    let mut term = Terminal::new(core, prompt);
+pub fn expand_prompt(raw: &str) -> String {
+    let ansi_on_prompt = oct_to_hex_in_str(raw);
+    Terminal::make_prompt_string(&ansi_on_prompt)
+        .replace("\\[", "").replace("\\]", "")
+}
+
+pub fn read_line(core: &mut ShellCore, prompt: &str, preload: &str) -> Result<String, InputError>{
+    let raw_prompt = core.db.get_param(prompt).unwrap_or(String::new());
+    edit_line(core, &raw_prompt, preload)
+}
+
+pub fn edit_line(core: &mut ShellCore, raw_prompt: &str, preload: &str) -> Result<String, InputError>{
+    core.history.insert(0, HistoryEntry::default());
+    let ans = edit_line_core(core, raw_prompt, preload);
+    core.history.remove(0);
+    ans
+}
+
+fn edit_line_core(core: &mut ShellCore, raw_prompt: &str, preload: &str) -> Result<String, InputError>{
+    let mut term = Terminal::new(raw_prompt);
+    if ! preload.is_empty() {
+        term.chars.extend(preload.chars());
+        term.head = term.chars.len();
+        term.rewrite(false);
+    }
     let mut term_size = Terminal::size();
-    core.history.insert(0, String::new());
     let mut prev_key = event::Key::Char('a');
     let mut tab_num = 0;
 
@@ -443,8 +465,5 @@ pub fn read_line(core: &mut ShellCore, prompt: &str) -> Result<String, InputErro
         }
     }
 
-    core.history[0] = term.get_string(term.prompt.chars().count());
-    core.history[0].pop();
-
     Ok(term.get_string(term.prompt.chars().count()))
 }
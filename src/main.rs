@@ -46,8 +46,9 @@ fn read_rc_file(core: &mut ShellCore) {
     }
 }
 
-fn configure(args: &Vec<String>) -> ShellCore {
+fn configure(args: &Vec<String>, login: bool) -> ShellCore {
     let mut core = ShellCore::new();
+    core.shopts.set("login_shell", login);
     let mut parameters = vec![args[0].clone()];
     let mut options = vec![];
 
@@ -72,12 +73,16 @@ fn main() {
         show_version();
     }
 
+    let login = args[0].starts_with("-")
+                | arg::consume_option("--login", &mut args)
+                | arg::consume_option("-l", &mut args);
+
     let c_parts = arg::consume_with_subsequents("-c", &mut args);
     if c_parts.len() != 0 {
-        run_and_exit_c_option(&args, &c_parts);
+        run_and_exit_c_option(&args, &c_parts, login);
     }
 
-    let mut core = configure(&args);
+    let mut core = configure(&args, login);
     signal::run_signal_check(&mut core);
 
     if core.script_name == "-" {
@@ -87,14 +92,14 @@ fn main() {
 }
 
 fn set_history(core: &mut ShellCore, s: &str) {
-    if core.read_stdin || core.history.is_empty() {
+    if core.read_stdin || ! core.db.flags.contains('i') {
         return;
     }
 
-    core.history[0] = s.trim_end().replace("\n", "↵ \0").to_string();
-    if core.history[0].is_empty()
-    || (core.history.len() > 1 && core.history[0] == core.history[1]) {
-        core.history.remove(0);
+    let s = s.trim_end().replace("\n", "↵ \0");
+    if core.history_control(&s) {
+        core.add_history(s.trim_start());
+        core.sync_history();
     }
 }
 
@@ -109,16 +114,19 @@ fn main_loop(core: &mut ShellCore) {
 
     if core.script_name != "-" {
         core.db.flags.retain(|f| f != 'i');
-        feeder.set_file(&core.script_name);
+        core.script_fd = Some(feeder.set_file(&core.script_name));
     }
 
     if core.db.flags.contains('i') {
         show_message();
+        let filename = core.db.get_param("HISTFILE").unwrap_or_default();
+        let _ = core.read_history_file(&filename, false);
     }
 
     loop {
         core.jobtable_check_status();
         core.jobtable_print_status_change();
+        core.sync_history();
 
         match feeder.feed_line(core) {
             Ok(()) => {}, 
@@ -126,31 +134,45 @@ fn main_loop(core: &mut ShellCore) {
                 signal::input_interrupt_check(&mut feeder, core);
                 continue;
             },
-            _ => break,
+            Err(InputError::Canceled) => continue,
+            _ => match core.jobtable_check_before_exit() {
+                true  => continue,
+                false => break,
+            },
         }
 
         core.word_eval_error = false;
         core.sigint.store(false, Relaxed);
         match Script::parse(&mut feeder, core, false){
             Some(mut s) => {
-                s.exec(core);
+                let warned = core.exit_warned;
                 set_history(core, &s.get_text());
+                s.exec(core);
+                if warned {
+                    core.exit_warned = false;
+                }
+                if core.word_eval_error && core.db.flags.contains('u')
+                && ! core.db.flags.contains('i') {
+                    break;
+                }
             },
             None => {},
         }
+        core.check_caught_signals();
         core.sigint.store(false, Relaxed);
     }
     core.write_history_to_file();
     exit::normal(core);
 }
 
-fn run_and_exit_c_option(args: &Vec<String>, c_parts: &Vec<String>) {
+fn run_and_exit_c_option(args: &Vec<String>, c_parts: &Vec<String>, login: bool) {
     if c_parts.len() < 2 {
         println!("{}: -c: option requires an argument", &args[0]);
         process::exit(2);                
     }
 
     let mut core = ShellCore::new();
+    core.shopts.set("login_shell", login);
     let parameters = if c_parts.len() > 2 {
         c_parts[2..].to_vec()
     }else{
@@ -1,7 +1,7 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::{exit, Feeder, Script, ShellCore, signal};
+use crate::{exit, utils, Feeder, Script, ShellCore, signal};
 use crate::utils::error;
 use nix::unistd;
 use nix::errno::Errno;
@@ -11,7 +11,7 @@ use nix::sys::signal::Signal;
 use nix::sys::wait::{WaitPidFlag, WaitStatus};
 use nix::time::{clock_gettime, ClockId};
 use nix::unistd::Pid;
-use std::process;
+use std::{env, process};
 use std::ffi::CString;
 use std::sync::atomic::Ordering::Relaxed;
 
@@ -23,8 +23,10 @@ pub fn wait_pipeline(core: &mut ShellCore, pids: Vec<Option<Pid>>,
         }
         if exclamation {
             core.flip_exit_status();
+        }else{
+            core.run_err_trap();
+            exit::check_e_option(core);
         }
-        exit::check_e_option(core);
         return vec![];
     }
 
@@ -51,12 +53,13 @@ pub fn wait_pipeline(core: &mut ShellCore, pids: Vec<Option<Pid>>,
         }
     }
 
-    if exclamation {
+    if exclamation { // neither ERR nor -e applies to a negated pipeline
         core.flip_exit_status();
+    }else{
+        core.run_err_trap();
+        exit::check_e_option(core);
     }
 
-    exit::check_e_option(core);
-
     ans
 }
 
@@ -92,6 +95,25 @@ fn wait_process(core: &mut ShellCore, child: Pid) -> WaitStatus {
     ws.expect("SUSH INTERNAL ERROR: no wait status")
 }
 
+pub fn close_process_substitutions(core: &mut ShellCore, from: usize) {
+    if core.process_substitutions.len() > from {
+        let procsubs = core.process_substitutions.split_off(from);
+        for (fd, _, _) in &procsubs {
+            let _ = unistd::close(*fd);
+        }
+        for (_, pid, output) in procsubs {
+            match output {
+                true  => { let _ = wait::waitpid(pid, None); }, //ends at EOF of the pipe
+                false => core.process_substitution_pids.push(pid),
+            }
+        }
+    }
+
+    core.process_substitution_pids.retain(|pid|
+        wait::waitpid(*pid, Some(WaitPidFlag::WNOHANG)) == Ok(WaitStatus::StillAlive)
+    );
+}
+
 pub fn set_foreground(core: &ShellCore) {
     let fd = match core.tty_fd.as_ref() {
         Some(fd) => fd,
@@ -136,17 +158,46 @@ fn show_time(core: &ShellCore) {
 }
 
 pub fn exec_command(args: &Vec<String>, core: &mut ShellCore) -> ! {
-    let cargs = to_cargs(args);
-
-    match unistd::execvp(&cargs[0], &cargs) {
-        Err(Errno::E2BIG) => exit::arg_list_too_long(&args[0], core),
-        Err(Errno::EACCES) => exit::permission_denied(&args[0], core),
-        Err(Errno::ENOENT) => run_command_not_found(&args[0], core),
-        Err(err) => {
+    match replace_process(&args[0], args, false, core) {
+        Errno::E2BIG => exit::arg_list_too_long(&args[0], core),
+        Errno::EACCES => exit::permission_denied(&args[0], core),
+        Errno::ENOENT => run_command_not_found(&args[0], core),
+        err => {
             eprintln!("Failed to execute. {:?}", err);
             process::exit(127)
         }
-        _ => exit::internal("never come here")
+    }
+}
+
+/* returns only when execvp fails */
+pub fn replace_process(command: &str, args: &[String],
+                       clear_env: bool, core: &mut ShellCore) -> Errno {
+    let cargs = to_cargs(args);
+    let command = match CString::new(command) {
+        Ok(c) => c,
+        Err(_) => return Errno::ENOENT,
+    };
+
+    let result = match clear_env {
+        true  => unistd::execvpe(&command, &cargs, &[] as &[CString]),
+        false => {
+            let exported = core.db.get_exported_params();
+            for (name, _) in env::vars() {
+                if utils::is_param(&name) && name != "_"
+                && ! exported.iter().any(|e| e.0 == name) {
+                    env::remove_var(name);
+                }
+            }
+            for (name, val) in exported {
+                env::set_var(name, val);
+            }
+            unistd::execvp(&command, &cargs)
+        },
+    };
+
+    match result {
+        Err(err) => err,
+        Ok(_) => exit::internal("never come here"),
     }
 }
 
@@ -162,7 +213,7 @@ fn run_command_not_found(arg: &String, core: &mut ShellCore) -> ! {
     exit::not_found(&arg, core)
 }
 
-fn to_cargs(args: &Vec<String>) -> Vec<CString> {
+fn to_cargs(args: &[String]) -> Vec<CString> {
     args.iter()
         .map(|a| CString::new(a.to_string()).unwrap())
         .collect()
@@ -1,15 +1,20 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
+use nix::{fcntl, unistd};
+use nix::fcntl::OFlag;
+use nix::poll::{poll, PollFd, PollFlags, PollTimeout};
 use nix::sys::signal;
 use nix::sys::signal::{Signal, SigHandler};
-use std::{thread, time};
+use std::os::fd::{AsRawFd, BorrowedFd, RawFd};
+use std::thread;
 use std::sync::Arc;
 use std::sync::atomic::Ordering::Relaxed;
 use crate::core::ShellCore;
 use crate::feeder::Feeder;
-use signal_hook::consts;
+use signal_hook::{consts, low_level};
 use signal_hook::iterator::Signals;
+use std::str::FromStr;
 
 pub fn ignore(sig: Signal) {
     unsafe { signal::signal(sig, SigHandler::SigIgn) }
@@ -22,31 +27,87 @@ pub fn restore(sig: Signal) {
 }
 
 pub fn run_signal_check(core: &mut ShellCore) {
-    for fd in 3..10 { //use FD 3~9 to prevent signal-hool from using these FDs
-        nix::unistd::dup2(2, fd).expect("sush(fatal): init error");
+    let free_fds: Vec<i32> = (3..10).filter(|fd| fcntl::fcntl(*fd, fcntl::F_GETFD).is_err()).collect();
+    for fd in &free_fds { //use FD 3~9 to prevent signal-hool from using these FDs
+        nix::unistd::dup2(2, *fd).expect("sush(fatal): init error");
+    }
+
+    let mut signals = Signals::new(vec![consts::SIGINT])
+                      .expect("sush(fatal): cannot prepare signal data");
+    core.signal_handle = Some(signals.handle());
+
+    for fd in free_fds { // release FD 3~9 (the script is not put here. see Feeder::set_file)
+        nix::unistd::close(fd).expect("sush(fatal): init error");
     }
 
     let sigint = Arc::clone(&core.sigint); //追加
+    let trapped = Arc::clone(&core.trapped_signals);
+    let caught = Arc::clone(&core.caught_signals);
+    let wakeup = Arc::clone(&core.wakeup_fd);
  
     thread::spawn(move || {
-        let mut signals = Signals::new(vec![consts::SIGINT])
-                          .expect("sush(fatal): cannot prepare signal data");
-
-        for fd in 3..10 { // release FD 3~9
-            nix::unistd::close(fd).expect("sush(fatal): init error");
-        }
-
-        loop {
-            thread::sleep(time::Duration::from_millis(100)); //0.1秒周期に変更
-            for signal in signals.pending() {
-                if signal == consts::SIGINT {
-                    sigint.store(true, Relaxed);
+        for signal in signals.forever() {
+            if trapped.lock().unwrap().contains(&signal) {
+                caught.lock().unwrap().push(signal);
+                if let Some(fd) = *wakeup.lock().unwrap() {
+                    let _ = unistd::write(unsafe{BorrowedFd::borrow_raw(fd)}, &[0]);
                 }
+            }else if signal == consts::SIGINT {
+                sigint.store(true, Relaxed);
+            }else{
+                let _ = low_level::emulate_default_handler(signal);
             }
         }
     });
 } //thanks: https://dev.to/talzvon/handling-unix-kill-signals-in-rust-55g6
 
+fn prepare_wakeup_pipe(core: &mut ShellCore) -> RawFd {
+    let pid = unistd::getpid();
+    match core.wakeup_pipe {
+        Some((p, fd, _)) if p == pid => return fd,
+        Some((_, fd, id)) => { // inherited from the parent shell
+            low_level::unregister(id);
+            let _ = unistd::close(fd);
+            if let Some(w) = core.wakeup_fd.lock().unwrap().take() {
+                let _ = unistd::close(w);
+            }
+        },
+        None => {},
+    }
+
+    let (r, w) = unistd::pipe2(OFlag::O_CLOEXEC | OFlag::O_NONBLOCK)
+                 .expect("sush(fatal): cannot create a pipe for wait");
+    let r = fcntl::fcntl(r.as_raw_fd(), fcntl::F_DUPFD_CLOEXEC(255))
+            .expect("sush(fatal): cannot create a pipe for wait");
+    let w = fcntl::fcntl(w.as_raw_fd(), fcntl::F_DUPFD_CLOEXEC(255))
+            .expect("sush(fatal): cannot create a pipe for wait");
+
+    let id = low_level::pipe::register_raw(consts::SIGCHLD, w)
+             .expect("sush(fatal): cannot register SIGCHLD");
+    core.wakeup_pipe = Some((pid, r, id));
+    *core.wakeup_fd.lock().unwrap() = Some(w);
+    r
+}
+
+/* to be called before checking the children so that no SIGCHLD is missed */
+pub fn prepare_wakeup(core: &mut ShellCore) {
+    prepare_wakeup_pipe(core);
+}
+
+/* sleeps until a child changes its state or a trapped signal arrives */
+pub fn wait_wakeup(core: &mut ShellCore) {
+    let fd = prepare_wakeup_pipe(core);
+    let mut fds = [PollFd::new(unsafe{BorrowedFd::borrow_raw(fd)}, PollFlags::POLLIN)];
+    let _ = poll(&mut fds, PollTimeout::NONE);
+
+    let mut buf = [0; 64];
+    while let Ok(n) = unistd::read(fd, &mut buf) {
+        if n == 0 {
+            break;
+        }
+    }
+}
+
 pub fn input_interrupt_check(feeder: &mut Feeder, core: &mut ShellCore) -> bool {
     if ! core.sigint.load(Relaxed) { //core.input_interrupt {
         return false;
@@ -57,3 +118,15 @@ pub fn input_interrupt_check(feeder: &mut Feeder, core: &mut ShellCore) -> bool
     feeder.consume(feeder.len());
     true
 }
+
+pub fn to_signal(s: &str) -> Option<Signal> {
+    if let Ok(n) = s.parse::<i32>() {
+        return Signal::try_from(n).ok();
+    }
+
+    let name = s.to_uppercase();
+    match name.starts_with("SIG") {
+        true  => Signal::from_str(&name).ok(),
+        false => Signal::from_str(&("SIG".to_owned() + &name)).ok(),
+    }
+}
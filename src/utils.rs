@@ -3,6 +3,7 @@
 
 pub mod directory;
 pub mod clock;
+pub mod ere;
 pub mod error;
 pub mod exit;
 pub mod file;
@@ -10,12 +11,13 @@ pub mod file_check;
 pub mod glob;
 pub mod arg;
 pub mod random;
+pub mod ansi_c;
 
 use crate::{Feeder, ShellCore};
 
 pub fn reserved(w: &str) -> bool {
     match w {
-        "[[" | "]]" | "{" | "}" | "while" | "for" | "do" | "done" | "if" | "then" | "elif" | "else" | "fi" | "case" => true,
+        "[[" | "]]" | "{" | "}" | "while" | "until" | "for" | "select" | "do" | "done" | "if" | "then" | "elif" | "else" | "fi" | "case" | "coproc" => true,
         _ => false,
     }
 }
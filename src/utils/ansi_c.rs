@@ -0,0 +1,160 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+fn take_number(chars: &mut Peekable<Chars>, radix: u32, max_len: usize) -> Option<u32> {
+    let mut ans = None;
+    for _ in 0..max_len {
+        match chars.peek().and_then(|c| c.to_digit(radix)) {
+            Some(d) => {
+                ans = Some(ans.unwrap_or(0) * radix + d);
+                chars.next();
+            },
+            None => break,
+        }
+    }
+    ans
+}
+
+pub fn push_char(c: char, out: &mut Vec<u8>) {
+    let mut buf = [0; 4];
+    out.extend(c.encode_utf8(&mut buf).as_bytes());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    AnsiC,   // $'...'
+    Echo,    // echo -e
+    Printf,  // format string of printf
+    PrintfB, // argument of %b
+}
+
+/* reads the sequence after a backslash; returns false when \c stops the output */
+pub fn unescape_char(chars: &mut Peekable<Chars>, mode: Mode, out: &mut Vec<u8>) -> bool {
+    let e = match chars.next() {
+        Some(e) => e,
+        None => {
+            out.push(b'\\');
+            return true;
+        },
+    };
+    let literal = mode == Mode::AnsiC || mode == Mode::Printf;
+
+    match e {
+        'a' => out.push(0x07),
+        'b' => out.push(0x08),
+        'e' | 'E' => out.push(0x1b),
+        'f' => out.push(0x0c),
+        'n' => out.push(b'\n'),
+        'r' => out.push(b'\r'),
+        't' => out.push(b'\t'),
+        'v' => out.push(0x0b),
+        '\\' => out.push(b'\\'),
+        '\'' | '"' | '?' if literal => push_char(e, out),
+        'c' if mode == Mode::AnsiC => match chars.next() {
+            Some('?') => out.push(0x7f),
+            Some(c) => out.push((c.to_ascii_uppercase() as u8) & 0x1f),
+            None    => out.extend(b"\\c"),
+        },
+        'c' if mode != Mode::Printf => return false,
+        '0' if ! literal => out.push(take_number(chars, 8, 3).unwrap_or(0) as u8),
+        '0'..='7' if mode != Mode::Echo => {
+            let mut n = e.to_digit(8).unwrap();
+            for _ in 0..2 {
+                match chars.next_if(|c| c.is_digit(8)) {
+                    Some(d) => n = n * 8 + d.to_digit(8).unwrap(),
+                    None => break,
+                }
+            }
+            out.push(n as u8);
+        },
+        'x' => match take_number(chars, 16, 2) {
+            Some(n) => out.push(n as u8),
+            None    => out.extend(b"\\x"),
+        },
+        'u' | 'U' => {
+            let max_len = if e == 'u' { 4 } else { 8 };
+            match take_number(chars, 16, max_len) {
+                Some(n) => push_char(char::from_u32(n).unwrap_or('\u{FFFD}'), out),
+                None    => {
+                    out.push(b'\\');
+                    push_char(e, out);
+                },
+            }
+        },
+        _ => {
+            out.push(b'\\');
+            push_char(e, out);
+        },
+    }
+    true
+}
+
+pub fn unescape_to(s: &str, mode: Mode, out: &mut Vec<u8>) -> bool {
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            push_char(c, out);
+        }else if ! unescape_char(&mut chars, mode, out) {
+            return false;
+        }
+    }
+    true
+}
+
+pub fn unescape(s: &str) -> String {
+    let mut out = vec![];
+    unescape_to(s, Mode::AnsiC, &mut out);
+    String::from_utf8_lossy(&out).to_string()
+}
+
+fn escape(c: char) -> String {
+    match c {
+        '\x07' => "\\a".to_string(),
+        '\x08' => "\\b".to_string(),
+        '\x1b' => "\\E".to_string(),
+        '\x0c' => "\\f".to_string(),
+        '\n'   => "\\n".to_string(),
+        '\r'   => "\\r".to_string(),
+        '\t'   => "\\t".to_string(),
+        '\x0b' => "\\v".to_string(),
+        '\\'   => "\\\\".to_string(),
+        '\''   => "\\'".to_string(),
+        c if c.is_control() => format!("\\{:03o}", c as u32),
+        c => c.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Quote {
+    Single,    // 'a'\''b'
+    Double,    // "a\"b"
+    Backslash, // a\ b
+}
+
+/* a string with control characters is always quoted as $'...' */
+pub fn quote(s: &str, style: Quote) -> String {
+    if s.chars().any(|c| c.is_control()) {
+        return format!("$'{}'", s.chars().map(escape).collect::<String>());
+    }
+
+    let escape_with = |specials: &str, is_head_special: fn(char) -> bool| {
+        let mut ans = String::new();
+        for (i, c) in s.chars().enumerate() {
+            if specials.contains(c) || (i == 0 && is_head_special(c)) {
+                ans.push('\\');
+            }
+            ans.push(c);
+        }
+        ans
+    };
+
+    match style {
+        Quote::Single => format!("'{}'", s.replace("'", "'\\''")),
+        Quote::Double => format!("\"{}\"", escape_with("\\\"$`", |_| false)),
+        Quote::Backslash if s.is_empty() => "''".to_string(),
+        Quote::Backslash => escape_with(" '\"\\|&;()<>!{}*[?]^$`,", |c| c == '~' || c == '#'),
+    }
+}
@@ -16,6 +16,20 @@ pub fn consume_with_next_arg(prev_opt: &str, args: &mut Vec<String>) -> Option<S
     }
 }
 
+pub fn consume_option(opt: &str, args: &mut Vec<String>) -> bool {
+    let end = args.iter().skip(1)
+              .position(|a| a == "-c" || ! a.starts_with("-"))
+              .map(|p| p+1).unwrap_or(args.len());
+
+    match args[..end].iter().position(|a| a == opt) {
+        Some(pos) => {
+            args.remove(pos);
+            true
+        },
+        None => false,
+    }
+}
+
 pub fn consume_with_subsequents(prev_opt: &str, args: &mut Vec<String>) -> Vec<String> {
     match args.iter().position(|a| a == prev_opt) {
         Some(pos) => {
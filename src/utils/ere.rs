@@ -0,0 +1,97 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+fn is_interval(chars: &[char]) -> bool {
+    let s: String = chars.iter().skip(1).take_while(|c| **c != '}').collect();
+    if chars.len() < s.len() + 2 || s.is_empty() {
+        return false;
+    }
+
+    let mut nums = s.splitn(2, ',');
+    let low = nums.next().unwrap_or("");
+    let high = nums.next().unwrap_or("0");
+    ! low.is_empty() && low.chars().all(|c| c.is_ascii_digit())
+    && high.chars().all(|c| c.is_ascii_digit())
+}
+
+fn bracket(chars: &[char], ans: &mut String) -> usize {
+    let mut pos = 1;
+    ans.push('[');
+    if chars.get(pos) == Some(&'^') {
+        ans.push('^');
+        pos += 1;
+    }
+    if chars.get(pos) == Some(&']') {
+        ans.push_str("\\]");
+        pos += 1;
+    }
+
+    while pos < chars.len() && chars[pos] != ']' {
+        let c = chars[pos];
+        let next = chars.get(pos+1).copied().unwrap_or(' ');
+
+        if c == '[' && ":=.".contains(next) {
+            let rest: String = chars[pos+2..].iter().collect();
+            if let Some(end) = rest.find(&format!("{}]", next)) {
+                let inner = &rest[..end];
+                match next {
+                    ':' => ans.push_str(&format!("[:{}:]", inner)),
+                    _   => ans.push_str(&regex::escape(inner)),
+                }
+                pos += inner.chars().count() + 4;
+                continue;
+            }
+        }
+
+        match c {
+            '\\' | '[' | '&' | '~' => { ans.push('\\'); ans.push(c); },
+            '-' if next == '-' => ans.push_str("\\-"),
+            _ => ans.push(c),
+        }
+        pos += 1;
+    }
+
+    if pos < chars.len() {
+        ans.push(']');
+        pos += 1;
+    }
+    pos
+}
+
+pub fn to_rust_regex(pattern: &str) -> String {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut ans = String::new();
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        match chars[pos] {
+            '[' => pos += bracket(&chars[pos..], &mut ans),
+            '\\' => {
+                match chars.get(pos+1) {
+                    Some('<') | Some('>') => ans.push_str("\\b"),
+                    Some(c) if "wWsSbB".contains(*c) => { ans.push('\\'); ans.push(*c); },
+                    Some(c) => ans.push_str(&regex::escape(&c.to_string())),
+                    None => ans.push_str("\\\\"),
+                }
+                pos += 2;
+            },
+            '{' if is_interval(&chars[pos..]) => {
+                while chars[pos] != '}' {
+                    ans.push(chars[pos]);
+                    pos += 1;
+                }
+                ans.push('}');
+                pos += 1;
+            },
+            '}' => {
+                ans.push_str("\\}");
+                pos += 1;
+            },
+            c => {
+                ans.push(c);
+                pos += 1;
+            },
+        }
+    }
+    ans
+}
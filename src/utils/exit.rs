@@ -6,6 +6,11 @@ use crate::utils::error;
 use std::process;
 
 pub fn normal(core: &mut ShellCore) -> ! {
+    if ! core.is_subshell && core.db.flags.contains('i')
+    && core.shopts.query("login_shell") && core.shopts.query("huponexit") {
+        core.jobtable_hangup();
+    }
+    core.run_exit_trap();
     core.write_history_to_file();
     process::exit(core.db.exit_status%256)
 }
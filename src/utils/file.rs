@@ -4,8 +4,11 @@
 
 use crate::ShellCore;
 use crate::utils::file_check;
+use nix::errno::Errno;
+use nix::unistd;
 use std::env;
 use std::ffi::OsString;
+use std::io::Write;
 use std::path::{Path, PathBuf, Component};
 
 pub fn oss_to_name(oss: &OsString) -> String {
@@ -74,3 +77,20 @@ pub fn make_canonical_path(core: &mut ShellCore, path_str: &str) -> PathBuf {
     canonical
 }
 
+
+/* std::io::stdout ignores EBADF, so builtins that report write errors use this */
+pub fn write_stdout(buf: &[u8]) -> Result<(), String> {
+    let _ = std::io::stdout().flush();
+    let mut rest = buf;
+    while ! rest.is_empty() {
+        match unistd::write(std::io::stdout(), rest) {
+            Ok(n) => rest = &rest[n..],
+            Err(Errno::EINTR) => {},
+            Err(e) => {
+                let msg = std::io::Error::from(e).to_string();
+                return Err(msg.split(" (os error").next().unwrap_or_default().to_string());
+            },
+        }
+    }
+    Ok(())
+}
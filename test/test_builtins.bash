@@ -139,6 +139,52 @@ res=$($com <<< 'echo あ い う | while read a b ; do echo $a ; echo $b ; done'
 [ "$res" == "あ
 い う" ] || err $LINENO
 
+res=$($com <<< 'read a b <<< "  x  y  z  "; echo "[$a][$b]"')
+[ "$res" == "[x][y  z]" ] || err $LINENO
+
+res=$($com <<< 'IFS=: read a b c <<< "x::y"; echo "[$a][$b][$c]"')
+[ "$res" == "[x][][y]" ] || err $LINENO
+
+res=$($com <<< 'IFS=: read a b <<< "x:y:"; echo "[$a][$b]"; IFS=: read a b <<< "x:y:z:"; echo "[$b]"')
+[ "$res" == "[x][y]
+[y:z:]" ] || err $LINENO
+
+res=$($com <<< 'IFS= read a <<< "  q  "; echo "[$a]"; read <<< "  r  "; echo "[$REPLY]"')
+[ "$res" == "[  q  ]
+[  r  ]" ] || err $LINENO
+
+res=$($com <<< 'read a <<< "a\\ b\\\\c"; echo "[$a]"; read -r a <<< "a\\ b"; echo "[$a]"')
+[ "$res" == "[a b\c]
+[a\ b]" ] || err $LINENO
+
+res=$($com <<< 'read -a A <<< " a b  c "; echo ${#A[@]} ${A[2]}')
+[ "$res" == "3 c" ] || err $LINENO
+
+res=$($com <<< 'read -n 2 a <<< "xyz"; echo $a; read -d , a b <<< "1 2,3"; echo "[$a][$b]"')
+[ "$res" == "xy
+[1][2]" ] || err $LINENO
+
+res=$($com <<< 'read -N 4 a <<< "x y
+z"; echo "[$a]"')
+[ "$res" == "[x y
+]" ] || err $LINENO
+
+res=$($com <<< '{ read a; read b; echo $a $b; } <<< "1
+2"')
+[ "$res" == "1 2" ] || err $LINENO
+
+res=$($com <<< 'read -t 0.1 a < <(sleep 1); echo $?; echo x | { sleep 0.1; read -t 0; echo $?; }')
+[ "$res" == "142
+0" ] || err $LINENO
+
+res=$($com <<< 'read -u 3 a 3<<< hi; echo $a; read -u 9 a; echo $?')
+[ "$res" == "hi
+1" ] || err $LINENO
+
+res=$($com <<< 'read 1a <<< x; echo $?; echo -n x | { read a; echo $? $a; }')
+[ "$res" == "1
+1 x" ] || err $LINENO
+
 # set command
 
 res=$($com <<< 'set -- a b c ; echo $2')
@@ -180,7 +226,8 @@ res=$($com -c 'A=1 ; f () { local A=5 ; A=4 ; } ; f ; echo $A')
 res=$($com -c 'A=1 ; f () { local A ; declare -r A ; A=123 ; } ; f')
 [[ "$?" -eq 1 ]] || err $LINENO
 
-res=$($com -c 'f () { local A ; declare -r A ; A=123 ; } ; f; A=3 ; echo $A')
+res=$($com <<< 'f () { local A ; declare -r A ; A=123 ; } ; f
+A=3 ; echo $A')
 [[ "$res" -eq 3 ]] || err $LINENO
 
 res=$($com -c 'A=1 ; declare -r A ; f () { local A ; A=123 ; } ; f')
@@ -189,6 +236,85 @@ res=$($com -c 'A=1 ; declare -r A ; f () { local A ; A=123 ; } ; f')
 res=$($com -c 'A=1 ; declare -r A ; A=(3 4)')
 [[ "$?" -eq 1 ]] || err $LINENO
 
+res=$($com -c 'declare -i n=1+2; echo $n; n+=3; echo $n')
+[ "$res" == "3
+6" ] || err $LINENO
+
+res=$($com -c 'declare -l lo=ABC; declare -u up; up=abc; echo $lo $up')
+[ "$res" == "abc ABC" ] || err $LINENO
+
+res=$($com -c 'x=10; declare -n r=x; echo $r; r=20; echo $x')
+[ "$res" == "10
+20" ] || err $LINENO
+
+res=$($com -c 'declare -ir a=5; declare -a b=(x "y z"); declare -p a b')
+[ "$res" == 'declare -ir a="5"
+declare -a b=([0]="x" [1]="y z")' ] || err $LINENO
+
+res=$($com -c 'f () { declare a=1; declare -g b=2; } ; f; echo "[$a][$b]"')
+[ "$res" == "[][2]" ] || err $LINENO
+
+res=$($com -c 'f () { local x=1; declare -g x=2; echo $x; declare -g g=3; }; f; echo $x ${g:-unset}')
+[ "$res" == "1
+2 3" ] || err $LINENO
+
+res=$($com -c 'eval "declare x=1"; f () { eval "local y=2"; echo $y; }; f; echo $x ${y:-unset}')
+[ "$res" == "2
+1 unset" ] || err $LINENO
+
+res=$($com -c 'declare -A h=([k]=v ["x y"]="1 2"); h+=([j]=w); echo "${h[x y]}" ${h[k]} ${h[j]}')
+[ "$res" == "1 2 v w" ] || err $LINENO
+
+res=$($com -c 'f () { local -A h=([a]=1); echo ${h[a]}; }; f; declare -A g; g=([b]=2); echo ${g[b]}')
+[ "$res" == "1
+2" ] || err $LINENO
+
+res=$($com -c 'declare -A h; h[x y]=1; h[k]=v; s=$(declare -p h); unset h; eval "$s"; echo "${h[x y]}" ${h[k]}')
+[ "$res" == "1 v" ] || err $LINENO
+
+res=$($com -c 'declare -A h; h[x y]=1; declare -p h')
+[ "$res" == 'declare -A h=(["x y"]="1")' ] || err $LINENO
+
+res=$($com -c 'a=([2]=x y [0]=z); a+=([7]=q r); declare -p a')
+[ "$res" == 'declare -a a=([0]="z" [2]="x" [3]="y" [7]="q" [8]="r")' ] || err $LINENO
+
+res=$($com -c 'f () { :; } ; declare -F; declare -p f; echo $?')
+[ "$res" == "declare -f f
+1" ] || err $LINENO
+
+### export ###
+
+res=$($com -c 'export A=1; B=2; export B; sh -c "echo \$A\$B"; export -n A; sh -c "echo [\$A]"')
+[ "$res" == "12
+[]" ] || err $LINENO
+
+res=$($com -c 'export A=1; A=2 sh -c "echo \$A"; export -p | grep " A="')
+[ "$res" == '2
+declare -x A="1"' ] || err $LINENO
+
+res=$($com -c 'f(){ local -x V=1; env | grep ^V=; }; f; env | grep -c ^V=')
+[ "$res" == 'V=1
+0' ] || err $LINENO
+
+res=$(V=old $com -c 'f(){ local -x V=loc; env | grep ^V=; }; f; env | grep ^V=; export -n V; V=new; export V; env | grep ^V=; unset V; env | grep -c ^V=')
+[ "$res" == 'V=loc
+V=old
+V=new
+0' ] || err $LINENO
+
+### readonly ###
+
+res=$($com <<< 'readonly A=1; A=2
+echo $A; readonly -p | grep " A="')
+[ "$res" == '1
+declare -r A="1"' ] || err $LINENO
+
+res=$($com <<< 'readonly A=1
+A=2; echo after
+echo next' 2>&1)
+[ "$(echo "$res" | sed 's/^.*: line/line/')" == 'line 2: A: readonly variable
+next' ] || err $LINENO
+
 ### command ###
 
 res=$($com -c 'command cd /; pwd')
@@ -233,6 +359,135 @@ echo $OPTIND
 [[ "$res" == '1
 3' ]] || err $LINENO
 
+### echo ###
+
+res=$($com <<< 'echo -n a; echo b')
+[ "$res" == "ab" ] || err $LINENO
+
+res=$($com <<< 'echo -e "a\tb\x41\0102あ"')
+[ "$res" == "$(printf 'a\tbABあ')" ] || err $LINENO
+
+res=$($com <<< 'echo -e "a\cb" c; echo d')
+[ "$res" == "ad" ] || err $LINENO
+
+res=$($com <<< 'echo -E "a\tb"; echo -eE "a\tb"')
+[ "$res" == 'a\tb
+a\tb' ] || err $LINENO
+
+res=$($com <<< 'echo -- -n; echo -nx; echo -')
+[ "$res" == '-- -n
+-nx
+-' ] || err $LINENO
+
+res=$($com <<< 'shopt -s xpg_echo; echo "a\nb"; echo -E "a\nb"')
+[ "$res" == 'a
+b
+a\nb' ] || err $LINENO
+
+res=$($com <<< 'echo -e "\q\x"')
+[ "$res" == '\q\x' ] || err $LINENO
+
+res=$($com <<< 'echo hi >&-; echo $?; printf x >&-; echo $?' 2> /dev/null)
+[ "$res" == '1
+1' ] || err $LINENO
+
+res=$($com <<< 'echo hi >&-' 2>&1)
+[[ "$res" =~ 'echo: write error: Bad file descriptor' ]] || err $LINENO
+
+### test ###
+
+res=$($com <<< 'a=1; [ -v a ] && echo ok; test -v b || echo ng')
+[ "$res" == "ok
+ng" ] || err $LINENO
+
+res=$($com <<< 'a=(1 ""); a[5]=x; test -v "a[1]"; echo $?; test -v "a[2]"; echo $?; test -v "a[-1]"; echo $?')
+[ "$res" == "0
+1
+0" ] || err $LINENO
+
+res=$($com <<< 'declare -A h; h[key]=v; test -v "h[key]"; echo $?; test -v "h[no]"; echo $?')
+[ "$res" == "0
+1" ] || err $LINENO
+
+res=$($com <<< '[ ] || echo a; [ "" ] || echo b; [ x ] && echo c; [ ! ] && echo d; [ -n ] && echo e')
+[ "$res" == "a
+b
+c
+d
+e" ] || err $LINENO
+
+res=$($com <<< '[ 1 -eq 01 ] && echo a; [ "*" == x ] || echo b; [ ! a = b ] && echo c; [ "(" x ")" ] && echo d')
+[ "$res" == "a
+b
+c
+d" ] || err $LINENO
+
+res=$($com <<< '[ a -a "" ] || echo a; [ a -o "" ] && echo b; [ 1 -lt 2 -a 3 -gt 4 ] || echo c; [ ! "(" a = b ")" -a -d / ] && echo d')
+[ "$res" == "a
+b
+c
+d" ] || err $LINENO
+
+res=$($com <<< '[ x -eq 1 ]; echo $?; [ a b ]; echo $?; [ a b c ]; echo $?; [ a ; echo $?')
+[ "$res" == "2
+2
+2
+2" ] || err $LINENO
+
+res=$($com <<< 'test b \> a && echo ok; command -V [ test')
+[ "$res" == "ok
+[ is a shell builtin
+test is a shell builtin" ] || err $LINENO
+
+### mapfile ###
+
+res=$($com <<< 'mapfile A <<< "a
+b"; echo ${#A[@]}; echo -n "${A[0]}"')
+[ "$res" = "2
+a" ] || err $LINENO
+
+res=$($com <<< 'echo -e "a\nb\nc\nd" > /tmp/$$-mapfile; mapfile -t -n 2 -s 1 A < /tmp/$$-mapfile; echo ${A[@]}; rm /tmp/$$-mapfile')
+[ "$res" = "b c" ] || err $LINENO
+
+res=$($com <<< 'A=(x y z); mapfile -t -O 1 A <<< "a
+b"; echo ${A[@]}')
+[ "$res" = "x a b" ] || err $LINENO
+
+res=$($com <<< 'A=(x y z); readarray -t A <<< "a"; echo ${A[@]}; readarray -t <<< "b"; echo $MAPFILE')
+[ "$res" = "a
+b" ] || err $LINENO
+
+res=$($com <<< 'echo -n a:b:c | { mapfile -d : -t A; echo ${A[@]} ${#A[@]}; }')
+[ "$res" = "a b c 3" ] || err $LINENO
+
+res=$($com <<< 'mapfile -t -C "echo cb" -c 2 A <<< "a
+b
+c
+d"')
+[ "$res" = "cb 1 b
+cb 3 d" ] || err $LINENO
+
+res=$($com <<< 'mapfile -tu 5 A 5<<< "a
+b"; echo ${A[1]}')
+[ "$res" = "b" ] || err $LINENO
+
+res=$($com <<< '{ mapfile -t -n 1 A; read b; echo ${A[@]} $b; } <<< "a
+b"')
+[ "$res" = "a b" ] || err $LINENO
+
+res=$($com <<< 'f () { local -a A; mapfile -t A <<< "a"; echo ${A[@]}; }; f; echo "[${A[@]}]"')
+[ "$res" = "a
+[]" ] || err $LINENO
+
+res=$($com <<< 'readonly A; mapfile A <<< "a"')
+[ "$?" = "1" ] || err $LINENO
+
+res=$($com <<< 'mapfile -u 9 A')
+[ "$?" = "1" ] || err $LINENO
+
+res=$($com <<< 'mapfile -n x A')
+[ "$?" = "2" ] || err $LINENO
+
 ### printf ###
 
 res=$($com <<< 'printf -v a %s bbb &> /dev/null; echo $a')
@@ -251,6 +506,332 @@ res=$($com <<< 'printf -v a[3] bb cc dd &> /dev/null; echo ${a[@]}')
 res=$($com <<< 'printf -v a[3] bb cc dd &> /dev/null; echo ${a[3]}')
 [ "$res" = "bb" ] || err $LINENO
 
+res=$($com <<< 'printf "%s-%s\n" a b c')
+[ "$res" = "a-b
+c-" ] || err $LINENO
+
+res=$($com <<< 'printf "%d %i %d %d %d\n" 0x1f 010 "'"'"'A" -3 ""')
+[ "$res" = "31 8 65 -3 0" ] || err $LINENO
+
+res=$($com <<< 'printf "%x %X %o %#x %#o %u\n" 255 255 8 255 8 -1')
+[ "$res" = "ff FF 10 0xff 010 18446744073709551615" ] || err $LINENO
+
+res=$($com <<< 'printf "%05d|%+d|% d|%.3d|%-5s|%.2s|%5s\n" 42 42 42 7 ab abcdef x')
+[ "$res" = "00042|+42| 42|007|ab   |ab|    x" ] || err $LINENO
+
+res=$($com <<< 'printf "%*d|%-*d|%.*f\n" 5 1 4 2 2 3.14159')
+[ "$res" = "    1|2   |3.14" ] || err $LINENO
+
+res=$($com <<< 'printf "%5.2f|%-10.3e|%g|%g|%G|%#g\n" 3.14159 1234.5 0.0001 123456789 1e-10 1')
+[ "$res" = " 3.14|1.234e+03 |0.0001|1.23457e+08|1E-10|1.00000" ] || err $LINENO
+
+res=$($com <<< 'printf "%a %.2a\n" 1 3.3')
+[ "$res" = "0x8p-3 0xd.33p-2" ] || err $LINENO
+
+res=$($com <<< 'printf "%c|%b|%s\n" hello "a\tb\0101" "a\tb"')
+[ "$res" = "h|a	bA|a\tb" ] || err $LINENO
+
+res=$($com <<< 'printf "%b%s\n" "a\cb" c; echo')
+[ "$res" = "a" ] || err $LINENO
+
+res=$($com <<< 'printf "%q %q %q %q\n" "a b" "it'"'"'s" "" "~x"; printf "%q\n" "a
+b"')
+[ "$res" = "a\ b it\'s '' \~x
+\$'a\nb'" ] || err $LINENO
+
+res=$($com <<< 'printf "%(%Y-%m-%d)T\n" 0')
+[ "$res" = "$(TZ= date -d @0 +%Y-%m-%d)" -o "$res" = "$(date -d @0 +%Y-%m-%d)" ] || err $LINENO
+
+res=$($com <<< 'printf "%d|\n" 12abc abc 2> /dev/null; echo $?')
+[ "$res" = "12|
+0|
+1" ] || err $LINENO
+
+res=$($com <<< 'printf "a%y|b" 2> /dev/null; echo $?')
+[ "$res" = "a1" ] || err $LINENO
+
+res=$($com <<< 'printf -v "A[2]" "%s" hi; echo ${A[2]}; declare -A H; printf -v "H[k]" "%d" 3; echo ${H[k]}')
+[ "$res" = "hi
+3" ] || err $LINENO
+
+res=$($com <<< 'a=(1 2 3); i=0; printf -v "a[i+1]" "%s" x; printf -v "a[-1]" "%s" y; echo ${a[@]}; k=key; declare -A H; printf -v "H[$k]" "%d" 3; echo ${H[key]}')
+[ "$res" == "1 x y
+3" ] || err $LINENO
+
+res=$($com <<< 'a=(1 2 3); a[-3]=w; echo ${a[@]}; printf -v "a[-9]" "%s" z || echo NG')
+[ "$res" == "w 2 3
+NG" ] || err $LINENO
+
+res=$($com <<< 'printf -v 1x a; echo $?')
+[ "$res" = "2" ] || err $LINENO
+
+
+### trap ###
+
+res=$($com <<< 'trap "echo bye" EXIT; echo a')
+[ "$res" = "a
+bye" ] || err $LINENO
+
+res=$($com <<< 'trap "echo bye \$?" EXIT; false')
+[ "$?" = "1" ] || err $LINENO
+[ "$res" = "bye 1" ] || err $LINENO
+
+res=$($com <<< 'trap "echo err" ERR; false; false || true; if false; then :; else :; fi; true')
+[ "$res" = "err" ] || err $LINENO
+
+res=$($com <<< 'trap "echo debug" DEBUG; echo a')
+[ "$res" = "debug
+a" ] || err $LINENO
+
+res=$($com <<< 'f () { trap "echo ret" RETURN; }; f; echo a')
+[ "$res" = "ret
+a" ] || err $LINENO
+
+res=$($com <<< 'trap "echo usr1" USR1; kill -USR1 $$; sleep 0.1; echo a')
+[ "$res" = "usr1
+a" ] || err $LINENO
+
+res=$($com <<< 'trap "" USR1; kill -USR1 $$; sleep 0.1; echo a')
+[ "$res" = "a" ] || err $LINENO
+
+res=$($com <<< 'trap "echo usr1" SIGUSR1; trap - USR1; kill -USR1 $$; sleep 0.1; echo a')
+[ "$res" = "" ] || err $LINENO
+
+res=$($com <<< "trap 'echo \"a'\''b\"' INT EXIT; trap -p; trap -p INT")
+[ "$res" = "trap -- 'echo \"a'\''b\"' EXIT
+trap -- 'echo \"a'\''b\"' SIGINT
+trap -- 'echo \"a'\''b\"' SIGINT
+a'b" ] || err $LINENO
+
+res=$($com <<< 'trap -l | head -n 1')
+[ "$res" = "$(bash -c 'trap -l' | head -n 1)" ] || err $LINENO
+
+res=$($com <<< 'trap "echo x" AAA')
+[ "$?" = "1" ] || err $LINENO
+
+res=$($com <<< '(trap "echo in" EXIT); trap "echo out" EXIT; (echo sub)')
+[ "$res" = "in
+sub
+out" ] || err $LINENO
+
+res=$($com <<< 'trap "echo err \$LINENO" ERR
+echo a
+false
+if false; then :; fi
+while false; do :; done')
+[ "$res" = "a
+err 3" ] || err $LINENO
+
+res=$($com <<< 'trap "echo err \$LINENO" ERR
+! true
+! { false; true; }
+until true; do :; done
+if while false; do :; done; false; then :; fi
+set -e; ! true; echo alive')
+[ "$res" = "err 3
+alive" ] || err $LINENO
+
+res=$($com <<< 'trap "echo \$LINENO" DEBUG
+:
+:')
+[ "$res" = "2
+3" ] || err $LINENO
+
+res=$($com <<< '( trap "echo usr1" USR1; kill -USR1 $BASHPID; sleep 0.1; echo a )')
+[ "$res" = "usr1
+a" ] || err $LINENO
+
+res=$($com <<< 'trap "echo got" USR1; sleep 3 & A=$!; (sleep 0.2; kill -USR1 $$) & wait $A; echo $?; kill $A')
+[ "$(echo "$res" | head -n 2)" = "got
+138" ] || err $LINENO
+
+res=$($com <<< 'if false; then :; fi; echo $?')
+[ "$res" = "0" ] || err $LINENO
+
+### history ###
+
+res=$($com <<< 'HISTFILE=; history -s a; history -s b c; history -s d; history')
+[ "$res" = "    1  a
+    2  b c
+    3  d" ] || err $LINENO
+
+res=$($com <<< 'HISTFILE=; history -s a; history -s b; history -s c; history 2')
+[ "$res" = "    2  b
+    3  c" ] || err $LINENO
+
+res=$($com <<< 'HISTFILE=; history -s a; history -s b; history -s c; history -d 2; history -d -1; history')
+[ "$res" = "    1  a" ] || err $LINENO
+
+res=$($com <<< 'HISTFILE=; history -s a; history -d 5')
+[ "$?" = "1" ] || err $LINENO
+
+res=$($com <<< 'HISTFILE=; history -s a; history -c; history')
+[ "$res" = "" ] || err $LINENO
+
+res=$($com <<< 'HISTFILE=; history -s "echo a b"; history -s ls; history -p "!!" "!-2:$" "x!e"')
+[ "$res" = "ls
+b
+xecho a b" ] || err $LINENO
+
+res=$($com <<< 'HISTFILE=; history -p "!nosuch"')
+[ "$?" = "1" ] || err $LINENO
+
+h='HISTFILE=; history -s "echo a b c"; history -s "ls -l /usr/lib/foo.tar.gz"; history -s "grep abc file.txt"; '
+
+res=$($com <<< "$h"'history -p "!!" "!-2" "!1" "!ls" "!?abc?" "!#"')
+[ "$res" = "grep abc file.txt
+ls -l /usr/lib/foo.tar.gz
+echo a b c
+ls -l /usr/lib/foo.tar.gz
+grep abc file.txt" ] || err $LINENO
+
+res=$($com <<< "$h"'history -p "!!:0" "!$" "!^" "!ls:2" "!ls:1-2" "!1:*" "!1:2*" "!1:1-" "!?file?%"')
+[ "$res" = "grep
+file.txt
+abc
+/usr/lib/foo.tar.gz
+-l /usr/lib/foo.tar.gz
+a b c
+b c
+a b
+file.txt" ] || err $LINENO
+
+res=$($com <<< "$h"'history -p "!ls:$:h" "!ls:$:t" "!ls:$:r" "!ls:$:e" "!ls:$:t:r:r"')
+[ "$res" = "/usr/lib
+foo.tar.gz
+/usr/lib/foo.tar
+.gz
+foo" ] || err $LINENO
+
+res=$($com <<< "$h"'history -p "!!:s/abc/xyz/" "!1:gs/ /_/" "!!:s/abc/[&]/" "!1:s/a/A/:s/b/B/" "^abc^xyz" "^abc^xyz^:s/xyz/q/"')
+[ "$res" = "grep xyz file.txt
+echo_a_b_c
+grep [abc] file.txt
+echo A B c
+grep xyz file.txt
+grep q file.txt" ] || err $LINENO
+
+res=$($com <<< "$h"'history -p "!ls:q" "!1:x" "!!:p"')
+[ "$res" = "'ls -l /usr/lib/foo.tar.gz'
+'echo' 'a' 'b' 'c'
+grep abc file.txt" ] || err $LINENO
+
+res=$($com <<< "$h"'history -p "\\!!" "'"'!!'"'" '"'\"!!\"'"' "!" "! x" "a!=b" "\${!a}"')
+[ "$res" = "\\!!
+'!!'
+\"grep abc file.txt\"
+!
+! x
+a!=b
+\${!a}" ] || err $LINENO
+
+res=$($com <<< "$h"'histchars="@,"; history -p "@@" "@ls:$:t" "!!"')
+[ "$res" = "grep abc file.txt
+foo.tar.gz
+!!" ] || err $LINENO
+
+res=$($com <<< "$h"'histchars="!^%"; history -p "echo !! %comment !!"')
+[ "$res" = "echo grep abc file.txt %comment !!" ] || err $LINENO
+
+res=$($com <<< "$h"'history -p "!99"; echo $?; history -p "!1:5"; echo $?; history -p "!!:s/zzz/y/"; echo $?' 2> /dev/null)
+[ "$res" = "1
+1
+1" ] || err $LINENO
+
+res=$($com <<< "$h"'history -p "!99"' 2>&1)
+[[ "$res" =~ '!99: event not found' ]] || err $LINENO
+
+res=$($com <<< "$h"'history -p "!1:5"' 2>&1)
+[[ "$res" =~ ':5: bad word specifier' ]] || err $LINENO
+
+res=$($com <<< 'HISTFILE=; history -x')
+[ "$?" = "2" ] || err $LINENO
+
+res=$($com <<< 'HISTFILE=; history abc')
+[ "$?" = "1" ] || err $LINENO
+
+res=$($com <<< 'history -r /tmp/sush_nonexist_$$' 2>&1)
+[[ "$res" =~ "history: /tmp/sush_nonexist_".*": No such file or directory" ]] || err $LINENO
+
+res=$($com <<< 'HISTFILE=/tmp/sush_hist_$$; history -s a; history -s b; history -w; history -c; history -r; history; cat $HISTFILE; rm $HISTFILE')
+[ "$res" = "    1  a
+    2  b
+a
+b" ] || err $LINENO
+
+res=$($com <<< 'HISTFILE=/tmp/sush_hist_$$; HISTTIMEFORMAT="%Y "; history -s a; history -w; history -c; history -r; history | grep -c "^    1  [0-9]\{4\} a$"; grep -c "^#[0-9]*$" $HISTFILE; rm $HISTFILE')
+[ "$res" = "1
+1" ] || err $LINENO
+
+res=$($com <<< 'HISTFILE=/tmp/sush_hist_$$; history -s a; history -a; history -s b; history -a; echo c >> $HISTFILE; history -n; history; rm $HISTFILE')
+[ "$res" = "    1  a
+    2  b
+    3  c" ] || err $LINENO
+
+res=$($com <<< 'HISTFILE=/tmp/sush_hist_$$; HISTFILESIZE=2; history -s a; history -s b; history -s c; history -w; cat $HISTFILE; rm $HISTFILE')
+[ "$res" = "b
+c" ] || err $LINENO
+
+res=$($com <<< 'HISTFILE=/tmp/sush_hist_$$; seq 10 > $HISTFILE; history -r
+'"$com"' -c "history -r $HISTFILE; HISTFILESIZE=9; history -s longer_command_from_b; history -w $HISTFILE"
+history -n; history 2; cat $HISTFILE | wc -l; rm $HISTFILE')
+[ "$res" = "   10  10
+   11  longer_command_from_b
+9" ] || err $LINENO
+
+res=$($com <<< 'HISTFILE=/tmp/sush_hist_$$; seq 3 > $HISTFILE; history -r
+'"$com"' -c "history -s from_b; history -a $HISTFILE"
+history -n; history 2; rm $HISTFILE')
+[ "$res" = "    3  3
+    4  from_b" ] || err $LINENO
+
+res=$($com <<< 'HISTFILE=/tmp/sush_hist_$$; printf "l1\nl2\n" > $HISTFILE; history -r; compgen -h | grep -c "^l"; rm $HISTFILE')
+[ "$res" = "2" ] || err $LINENO
+
+res=$($com <<< 'shopt -s histshare; shopt histshare')
+[ "$res" = "histshare       on" ] || err $LINENO
+
+res=$($com <<< 'HISTFILE=/tmp/sush_hist_$$; printf "a\nb\n" > $HISTFILE; history -r
+'"$com"' -c "history -s c; history -s d; history -a $HISTFILE"
+history -n; history -n; history; rm $HISTFILE')
+[ "$res" = "    1  a
+    2  b
+    3  c
+    4  d" ] || err $LINENO
+
+res=$($com <<< 'HISTFILE=/tmp/sush_hist_$$; printf "a\nb\n" > $HISTFILE; history -r
+'"$com"' -c "history -r $HISTFILE; history -s c; history -w $HISTFILE"
+history -s x; history -n; history -a; history; cat $HISTFILE; rm $HISTFILE')
+[ "$res" = "    1  a
+    2  b
+    3  c
+    4  x
+a
+b
+c
+x" ] || err $LINENO
+
+res=$($com <<< 'HISTFILE=/tmp/sush_hist_$$; : > $HISTFILE
+flock $HISTFILE -c "sleep 0.3; echo from_b >> $HISTFILE" &
+sleep 0.1; history -s from_a; history -a; wait; cat $HISTFILE; rm $HISTFILE')
+[ "$(echo "$res" | tail -n 2)" = "from_b
+from_a" ] || err $LINENO
+
+res=$($com <<< 'HISTFILE=; HISTSIZE=2; history -s a; history -s b; history -s c; history')
+[ "$res" = "    2  b
+    3  c" ] || err $LINENO
+
+res=$($com <<< 'HISTFILE=; history -s a; history -s b; history -s c; HISTSIZE=2; history -s d; history -s e
+history -d 1; echo $?; history -d 3; history; history -p "!3"; history -c; history -s f; history' 2> /dev/null)
+[ "$res" = "1
+    3  e
+e
+    1  f" ] || err $LINENO
+
+res=$($com <<< 'HISTFILE=/tmp/sush_hist_$$; seq 100000 > $HISTFILE; HISTSIZE=3; history -s x; history -r; history; rm $HISTFILE')
+[ "$res" = "99999  99999
+100000  100000
+100001  x" ] || err $LINENO
 
 echo $0 >> ./ok
 
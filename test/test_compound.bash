@@ -175,6 +175,65 @@ res=$($com <<< 'touch /tmp/rusty_bash_x ; while [ -f /tmp/rusty_bash_x ] ; do ec
 [ "$res" == "wait
 wait" ] || err $LINENO
 
+### UNTIL TEST ###
+
+res=$($com <<< 'i=0 ; until [ $i -ge 3 ] ; do echo $i ; i=$((i+1)) ; done')
+[ "$res" == "0
+1
+2" ] || err $LINENO
+
+res=$($com <<< 'until true ; do echo do not come here ; done')
+[ "$?" == 0 ] || err $LINENO
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'until false ; do echo a ; break ; done')
+[ "$res" == "a" ] || err $LINENO
+
+### SELECT TEST ###
+
+res=$($com <<< 'select a in x y z ; do echo "$a $REPLY" ; [ "$REPLY" = 3 ] && break ; done <<EOF
+1
+
+9
+3
+EOF' 2> /dev/null)
+[ "$res" == "x 1
+ 9
+z 3" ] || err $LINENO
+
+res=$($com <<< 'PS3="> " ; select a in x y ; do echo $a ; break ; done <<< 2' 2>&1)
+[ "$res" == "1) x
+2) y
+> y" ] || err $LINENO
+
+res=$($com <<< 'select a in x ; do echo $a ; break ; done <<< 1' 2>&1)
+[ "$res" == "1) x
+#? x" ] || err $LINENO
+
+res=$($com <<< 'set -- a b ; select v ; do echo $v ; done <<< 1' 2> /dev/null)
+[ "$?" == 1 ] || err $LINENO
+[ "$res" == "a" ] || err $LINENO
+
+### COPROC TEST ###
+
+res=$($com <<< 'coproc cat; echo abc >&${COPROC[1]}; head -n 1 <&${COPROC[0]}')
+[ "$res" == "abc" ] || err $LINENO
+
+res=$($com -c 'coproc UP { while read l ; do echo "[$l]" ; done ; }
+echo abc >&${UP[1]}
+head -n 1 <&${UP[0]}')
+[ "$res" == "[abc]" ] || err $LINENO
+
+res=$($com -c 'coproc echo a b; cat <&${COPROC[0]}')
+[ "$res" == "a b" ] || err $LINENO
+
+res=$($com <<< 'coproc NAME { sleep 1; }; echo ${#NAME[@]}; [ -n "$NAME_PID" ] && echo ok')
+[ "$res" == "2
+ok" ] || err $LINENO
+
+res=$($com <<< 'coproc sleep 1; jobs')
+echo "$res" | grep -F 'Running' | grep -F 'coproc sleep 1' || err $LINENO
+
 ### FOR TEST ###
 
 res=$($com <<< 'set a b c ; for x ; do echo $x ; done')
@@ -264,7 +323,7 @@ res=$($com <<< 'if [ "a" == "b" ] ; then echo aa; else echo bb; fi')
 [ "$res" = "bb" ] || err $LINENO
 
 res=$($com <<< 'if [ "a" == "b" ] ; then echo aa; fi' || echo x)
-[ "$res" = "x" ] || err $LINENO
+[ "$res" = "" ] || err $LINENO
 
 res=$($com <<< 'if [ "a" == "b" ] ; then echo a ; fi ; if [ "b" == "b" ] ; then echo bb ; fi')
 [ "$res" = "bb" ] || err $LINENO
@@ -880,6 +939,16 @@ res=$($com -c 'A= ; [[ -v A ]]')
 res=$($com -c 'B=A; A= ; [[ -v $B ]]')
 [ "$?" = "0" ] || err $LINENO
 
+res=$($com -c 'a=(1 ""); a[5]=x; i=4; [[ -v a[1] ]]; echo $?; [[ -v a[2] ]]; echo $?; [[ -v a[i+1] ]]; echo $?')
+[ "$res" = "0
+1
+0" ] || err $LINENO
+
+res=$($com -c 'declare -A h; h[key]=v; k=key; [[ -v h[key] ]]; echo $?; [[ -v h[no] ]]; echo $?; [[ -v h[$k] ]]; echo $?')
+[ "$res" = "0
+1
+0" ] || err $LINENO
+
 res=$($com -c '[[ -v "$B" ]]')
 [ "$?" = "1" ] || err $LINENO
 
@@ -959,6 +1028,42 @@ res=$($com -c 'A=あいう ; [[ A =~ あ ]]')
 res=$($com -c 'A=あいう ; [[ $A =~ * ]]')
 [ "$?" = "2" ] || err $LINENO
 
+res=$($com -c '[[ abc123 =~ ([a-z]+)([[:digit:]]+) ]]; echo "${BASH_REMATCH[@]}" ${#BASH_REMATCH[@]}')
+[ "$res" = "abc123 abc 123 3" ] || err $LINENO
+
+res=$($com -c '[[ abc =~ x ]]; echo $? ${#BASH_REMATCH[@]}')
+[ "$res" = "1 0" ] || err $LINENO
+
+res=$($com -c '[[ ab =~ (a)(x)?b ]]; echo "[${BASH_REMATCH[2]}]"')
+[ "$res" = "[]" ] || err $LINENO
+
+res=$($com -c '[[ abc =~ a"."c ]]')
+[ "$?" = "1" ] || err $LINENO
+
+res=$($com -c '[[ a.c =~ a"."c ]]')
+[ "$?" = "0" ] || err $LINENO
+
+res=$($com -c "[[ 'a(b' =~ '(' ]]")
+[ "$?" = "0" ] || err $LINENO
+
+res=$($com -c '[[ a\\b =~ [\\] ]]')
+[ "$?" = "0" ] || err $LINENO
+
+res=$($com -c '[[ ABC =~ ^abc$ ]]')
+[ "$?" = "1" ] || err $LINENO
+
+res=$($com -c 'shopt -s nocasematch; [[ ABC =~ ^abc$ ]]')
+[ "$?" = "0" ] || err $LINENO
+
+res=$($com -c 'r="a{"; [[ a =~ $r ]]')
+[ "$?" = "2" ] || err $LINENO
+
+res=$($com -c '[[ ${x:?msg} =~ a ]]')
+[ "$?" = "2" ] || err $LINENO
+
+res=$($com -c '[[ $e =~ ^$ ]]')
+[ "$?" = "0" ] || err $LINENO
+
 # and or 
 
 res=$($com -c '[[ -a /etc/passwd && -a /etc/passwd ]]')
@@ -23,4 +23,133 @@ echo "$res" | grep -F '[3]+ ' || err $LINENO
 res=$($com <<< 'sleep 5 | rev | cat & sleep 1 ; killall -SIGSTOP cat ; jobs')
 echo "$res" | grep Stopped || err $LINENO
 
+### KILL ###
+
+res=$($com <<< 'kill -l 9; kill -l 143; kill -l SIGHUP; kill -l TERM')
+[ "$res" == "KILL
+TERM
+1
+15" ] || err $LINENO
+
+res=$($com <<< 'kill -l | head -n 1')
+[ "$res" == "$(bash -c 'kill -l' | head -n 1)" ] || err $LINENO
+
+res=$($com <<< 'sleep 5 & kill %1; sleep 0.1; jobs')
+echo "$res" | grep -F '[1]+  Terminated' || err $LINENO
+
+res=$($com <<< 'sleep 5 & sleep 6 & kill -s KILL %?5; kill -9 %sleep\ 6; sleep 0.1; jobs')
+echo "$res" | grep -F '[1]-  Killed' || err $LINENO
+echo "$res" | grep -F '[2]+  Killed' || err $LINENO
+
+res=$($com <<< 'sleep 5 | cat & kill -n 15 %%; sleep 0.1; jobs')
+echo "$res" | grep -F 'Terminated' || err $LINENO
+
+res=$($com <<< 'kill -0 $$; echo $?; kill %1; echo $?; kill abc; echo $?; kill -FOO $$; echo $?; kill; echo $?')
+[ "$res" == "0
+1
+1
+1
+2" ] || err $LINENO
+
+res=$($com <<< 'kill %1' 2>&1)
+echo "$res" | grep -F 'kill: %1: no such job' || err $LINENO
+
+### DISOWN AND SUSPEND ###
+
+res=$($com <<< 'sleep 5 & sleep 6 & disown; jobs')
+echo "$res" | grep -F '[1]' || err $LINENO
+echo "$res" | grep -F '[2]' && err $LINENO
+
+res=$($com <<< 'sleep 5 & sleep 6 & disown %1 %2; jobs; echo $?')
+[ "$res" == "0" ] || err $LINENO
+
+res=$($com <<< 'sleep 5 & disown -h %1; jobs; kill %1')
+echo "$res" | grep -F '[1]+  Running' || err $LINENO
+
+res=$($com <<< 'sleep 5 & sleep 6 & kill -STOP %1; sleep 0.1; disown -r; jobs; kill -9 %1')
+echo "$res" | grep -F '[1]' || err $LINENO
+echo "$res" | grep -F '[2]' && err $LINENO
+
+res=$($com <<< 'sleep 5 & sleep 6 & disown -a; jobs; disown; echo $?; disown %3; echo $?; disown -x; echo $?')
+[ "$res" == "1
+1
+2" ] || err $LINENO
+
+res=$($com -l -c 'shopt login_shell; suspend; echo $?; suspend -x; echo $?')
+[ "$res" == "login_shell     on
+1
+2" ] || err $LINENO
+
+res=$($com -c 'echo "[$@]"; shopt login_shell' x -l --login a)
+[ "$res" == "[-l --login a]
+login_shell     off" ] || err $LINENO
+
+echo 'echo "[$@]"; shopt login_shell' > /tmp/$$-login
+res=$($com /tmp/$$-login -l foo)
+[ "$res" == "[-l foo]
+login_shell     off" ] || err $LINENO
+
+res=$($com --login /tmp/$$-login -l)
+[ "$res" == "[-l]
+login_shell     on" ] || err $LINENO
+rm -f /tmp/$$-login
+
+res=$($com -c 'shopt -s checkjobs; shopt -s huponexit; shopt checkjobs; shopt huponexit')
+[ "$res" == "checkjobs       on
+huponexit       on" ] || err $LINENO
+
+### JOBS AND WAIT ###
+
+res=$($com <<< 'sleep 5 & jobs -p; echo $!; kill %1')
+[ "$(echo "$res" | uniq | wc -l)" == "1" ] || err $LINENO
+
+res=$($com <<< 'sleep 5 & A=$!; disown $A; echo $?; kill $A')
+[ "$res" == "0" ] || err $LINENO
+
+res=$($com <<< '(exit 3) &
+sleep 0.1
+wait $!; echo $?')
+[ "$(echo "$res" | tail -n 1)" == "3" ] || err $LINENO
+
+res=$($com <<< 'sleep 1 & (exit 4) & wait -n -p V; echo $? $(( V == $! )); wait; echo $?')
+[ "$res" == "4 1
+0" ] || err $LINENO
+
+res=$($com <<< 'trap "echo got" USR1; sleep 3 & A=$!; (sleep 0.2; kill -USR1 $$) & wait -n $A; echo $?; kill $A')
+[ "$(echo "$res" | head -n 2)" == "got
+138" ] || err $LINENO
+
+res=$($com <<< 'wait -n; echo $?; wait 99999; echo $?; wait %3; echo $?; wait -p 1a; echo $?')
+[ "$res" == "127
+127
+127
+1" ] || err $LINENO
+
+res=$($com <<< 'sleep 5 & sleep 6 & kill -STOP %1; sleep 0.1; jobs -r; jobs -s; kill -9 %1 %2')
+echo "$res" | grep -F '[2]+  Running' || err $LINENO
+echo "$res" | grep -F '[1]-  Stopped' || err $LINENO
+[ "$(echo "$res" | grep -c -E 'Running|Stopped')" == "2" ] || err $LINENO
+
+res=$($com <<< 'sleep 5 & jobs -l %1; jobs %2; echo $?; jobs -y; echo $?; kill %1')
+echo "$res" | grep -E '^\[1\]\+ [0-9]+ Running' || err $LINENO
+echo "$res" | grep -E '^1$' || err $LINENO
+echo "$res" | grep -E '^2$' || err $LINENO
+
+res=$($com <<< 'sleep 0.1 & sleep 0.3; jobs -n; jobs -n; echo end')
+[ "$(echo "$res" | grep -c Done)" == "1" ] || err $LINENO
+
+res=$($com <<< 'sleep 5 & [ "$(jobs -p)" == "$!" ]; echo $?; jobs -p | grep -c "^$!$"; (jobs -p | wc -l); kill $(jobs -p); wait %1; echo $?' 2> /dev/null)
+[ "$res" == "0
+1
+0
+143" ] || err $LINENO
+
+res=$($com <<< 'sleep 5 & echo $(wait %1; echo $?) $(wait -n; echo $?); kill %1' 2> /dev/null)
+[ "$res" == "127 127" ] || err $LINENO
+
+res=$($com <<< '(exit 3) &
+sleep 0.1
+wait; wait $!; echo $?' 2> /dev/null)
+[ "$(echo "$res" | tail -n 1)" == "127" ] || err $LINENO
+
 echo $0 >> ./ok
@@ -66,6 +66,99 @@ res=$($com <<< 'set -o pipefail; set -e; false | true ; echo NG')
 res=$($com <<< 'set +B; echo {a,b}')
 [ "$res" == "{a,b}" ] || err $LINENO
 
+res=$($com <<< 'set +o braceexpand; echo {a,b}; set -o braceexpand; echo {a,b}')
+[ "$res" == "{a,b}
+a b" ] || err $LINENO
+
+### -o
+
+res=$($com <<< 'set -o errexit; echo $-; [[ -o errexit ]] && echo OK')
+[ "$res" == "Be
+OK" ] || err $LINENO
+
+res=$($com <<< 'set -eu; set -o | grep -E "^(errexit|nounset)"')
+[ "$res" == "errexit         on
+nounset         on" ] || err $LINENO
+
+res=$($com <<< 'set -e +e -o nounset; echo $SHELLOPTS')
+[ "$res" == "braceexpand:nounset" ] || err $LINENO
+
+res=$($com <<< 'set -o xtrace; set +o xtrace; echo $-')
+[ "$res" == "B" ] || err $LINENO
+
+res=$($com <<< 'set -h; echo $?; set -o monitor; echo $?; echo $- $SHELLOPTS; set +h +m; echo $?; echo $-')
+[ "$res" == "0
+0
+Bhm braceexpand:hashall:monitor
+0
+B" ] || err $LINENO
+
+res=$($com <<< 'set -o foo')
+[ "$?" == "2" ] || err $LINENO
+
+### -u
+
+res=$($com <<< 'set -u; echo $aaa; echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -o nounset; echo ${aaa}NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -u; echo ${aaa:-OK} ${aaa:+NG}')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'set -u; echo $@ $* ${@} $# OK')
+[ "$res" == "0 OK" ] || err $LINENO
+
+res=$($com -c 'set -u; echo $1 $2' a)
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -u; aaa=; echo ${aaa}OK')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'set -u; a=(1); echo ${a[0]}; echo ${a[3]}; echo NG' 2>&1)
+echo "$res" | grep -q "a\[3\]: unbound variable" || err $LINENO
+echo "$res" | grep -q NG && err $LINENO
+
+res=$($com <<< 'set -u; a=(1); b=(); declare -A h; h[k]=v; echo ${a[3]:-OK} ${#a[@]} ${b[@]} ${h[k]}')
+[ "$res" == "OK 1 v" ] || err $LINENO
+
+res=$($com <<< 'set -u; declare -A h; h[k]=v; echo ${h[x]}; echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -u; a[1]=x; echo $a; echo NG')
+[ "$res" == "" ] || err $LINENO
+
+### -f
+
+res=$($com <<< 'set -f; echo /et*; set +f; echo /et*')
+[ "$res" == "/et*
+/etc" ] || err $LINENO
+
+### -C
+
+res=$($com <<< 'set -C; echo a > /tmp/$$-clob; echo b > /tmp/$$-clob; cat /tmp/$$-clob; rm /tmp/$$-clob')
+[ "$res" == "a" ] || err $LINENO
+
+res=$($com <<< 'set -o noclobber; echo a > /tmp/$$-clob; echo b >| /tmp/$$-clob; cat /tmp/$$-clob; rm /tmp/$$-clob')
+[ "$res" == "b" ] || err $LINENO
+
+res=$($com <<< 'set -C; echo OK > /dev/null && echo OK')
+[ "$res" == "OK" ] || err $LINENO
+
+### -n
+
+res=$($com <<< 'set -n; echo NG')
+[ "$res" == "" ] || err $LINENO
+
+### -a
+
+res=$($com <<< 'set -a; A=OK; bash -c "echo \$A"')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'set -a; A=OK; set +a; B=NG; bash -c "echo \$A\$B"')
+[ "$res" == "OK" ] || err $LINENO
+
 
 
 echo $0 >> ./ok
@@ -54,6 +54,14 @@ res=$($com <<< 'echo ${A:=abc}; echo $A' )
 [ "$res" = "abc
 abc" ] || err $LINENO
 
+res=$($com <<< 'A=x; echo ${A:-abc}; echo ${A:-$((n=1))}$n' )
+[ "$res" = "x
+x" ] || err $LINENO
+
+res=$($com <<< 'A=x; echo ${A:=abc}; echo $A ${A:=$((n=1))}$n' )
+[ "$res" = "x
+x x" ] || err $LINENO
+
 res=$($com <<< 'echo ${A:="aaa
 bbb"}
 echo "$A"' )
@@ -211,6 +219,76 @@ res=$($com -c 'echo ${@[0]}' )
 [ $? = 1 ] || err $LINENO
 [ "$res" = "" ] || err $LINENO
 
+# case modification
+
+res=$($com -c 'A=hello; echo ${A^} ${A^^} ${A^^l} ${A^[a-h]}' )
+[ "$res" = "Hello HELLO heLLo Hello" ] || err $LINENO
+
+res=$($com -c 'A=WORLD; echo ${A,} ${A,,} ${A,,[OR]}' )
+[ "$res" = "wORLD world WorLD" ] || err $LINENO
+
+res=$($com -c 'A=(abc def); echo ${A[@]^} ${A[*]^^} ${A[1],,[E]}' )
+[ "$res" = "Abc Def ABC DEF def" ] || err $LINENO
+
+res=$($com -c 'A=(abc def); B=("${A[@]^}"); echo ${#B[@]} ${B[1]}' )
+[ "$res" = "2 Def" ] || err $LINENO
+
+res=$($com -c 'set abc def; echo ${@^} "${*^^}"' )
+[ "$res" = "Abc Def ABC DEF" ] || err $LINENO
+
+res=$($com -c 'declare -A A; A[k]=abc; echo ${A[k]^} ${A[@]^^}' )
+[ "$res" = "Abc ABC" ] || err $LINENO
+
+# transformation
+
+res=$($com <<< "A=\"it's\"; echo \${A@Q}; echo \${B@Q}end" )
+[ "$res" = "'it'\\''s'
+end" ] || err $LINENO
+
+res=$($com <<< 'A="x y"; eval "B=${A@Q}"; echo "$B"' )
+[ "$res" = "x y" ] || err $LINENO
+
+res=$($com -c 'A="a\tb\x41\101"; echo "${A@E}"' )
+[ "$res" = "a	bAA" ] || err $LINENO
+
+res=$($com -c 'A=abc; echo ${A@U} ${A@u} ${A@L}' )
+[ "$res" = "ABC Abc abc" ] || err $LINENO
+
+res=$($com -c 'A=v; echo ${A@A}; export B=1; echo ${B@A} ${B@a}' )
+[ "$res" = "A='v'
+declare -x B='1' x" ] || err $LINENO
+
+res=$($com -c 'A=(1 2); echo ${A@A}; echo ${A[@]@A}; echo ${A[@]@a}; echo ${A[@]@K}' )
+[ "$res" = 'declare -a A='"'1'"'
+declare -a A=([0]="1" [1]="2")
+a a
+0 "1" 1 "2"' ] || err $LINENO
+
+res=$($com -c 'A=([1]=x); echo ${A@A}; declare -A H=([k]=v); echo ${H@A} ${H[k]@A}' )
+[ "$res" = "declare -a A
+declare -A H declare -A H='v'" ] || err $LINENO
+
+res=$($com -c 'A=(x y); for w in ${A[@]@k}; do echo $w; done' )
+[ "$res" = '0
+x
+1
+y' ] || err $LINENO
+
+res=$($com -c 'B=(x y); echo ${B[1]@k} ${B@K}; declare -A h=([k]=v); echo "[${h@K}]" "${h[@]@k}"; set a b; echo ${@@K}' )
+[ "$res" = "'y' 'x'
+[] k v
+'a' 'b'" ] || err $LINENO
+
+res=$($com -c 'set p "q r"; echo ${@@Q}; echo ${*@A}' )
+[ "$res" = "'p' 'q r'
+set -- 'p' 'q r'" ] || err $LINENO
+
+res=$($com -c 'A="\u"; [ "${A@P}" = "$(id -un)" ]' )
+[ $? = 0 ] || err $LINENO
+
+res=$($com -c 'A=abc; echo ${A@Z}' )
+[ $? = 1 ] || err $LINENO
+
 ### IRREGULAR INPUT TEST ###
 
 res=$($com <<< 'eeeeeecho hoge')
@@ -510,6 +588,35 @@ res=$($com <<< "echo 123'abc'")
 res=$($com <<< "echo 123'abc'def")
 [ "$res" == "123abcdef" ] || err $LINENO
 
+res=$($com <<< "echo \$'a\\tb'")
+[ "$res" == "a	b" ] || err $LINENO
+
+res=$($com <<< "echo \$'\\e[31m\\x41\\101\\u3042\\U0001F363\\cA' | od -An -tx1 | tr -d ' '")
+[ "$res" == "1b5b33316d4141e38182f09f8da3010a" ] || err $LINENO
+
+res=$($com <<< "echo \$'\\c?\\c[' | od -An -tx1 | tr -d ' '")
+[ "$res" == "7f1b0a" ] || err $LINENO
+
+res=$($com <<< "echo \$'it\\'s' \$'a\\0b' \$'\\\\'")
+[ "$res" == "it's a \\" ] || err $LINENO
+
+res=$($com <<< "echo \$'a*' \$'' b | wc -w; echo \"\$'x'\"")
+[ "$res" == "2
+\$'x'" ] || err $LINENO
+
+res=$($com <<< "A=\$'x\\ny'; echo \"\$A\"")
+[ "$res" == "x
+y" ] || err $LINENO
+
+res=$($com <<< "[[ \$'\\t' == \$'\\x09' ]] && echo OK")
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< "case \$'a\\tb' in \$'a\\t'*) echo OK ;; esac")
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'A=abc; echo $"x $A y"')
+[ "$res" == "x abc y" ] || err $LINENO
+
 # parameter expansion
 
 res=$($com <<< 'echo $')
@@ -631,6 +738,44 @@ res=$($com <<< 'echo {,,}$(date "+%w")')
 res=$($com <<< 'echo $(date) | grep "  "')
 [ "$?" == "1" ] || err $LINENO
 
+# process substitution
+
+res=$($com <<< 'diff <(echo a) <(echo b) | wc -l')
+[ "$res" == "4" ] || err $LINENO
+
+res=$($com <<< 's=$SECONDS; echo <(sleep 3) > /dev/null; echo $(( $SECONDS - s < 2 ))')
+[ "$res" = "1" ] || err $LINENO
+
+res=$($com <<< 'echo abc > >(sleep 1; cat > /tmp/rusty_bash_ps); cat /tmp/rusty_bash_ps')
+[ "$res" = "abc" ] || err $LINENO
+
+res=$($com <<< 'echo <(true) | grep -c "^/dev/fd/[0-9]*$"')
+[ "$res" == "1" ] || err $LINENO
+
+res=$($com <<< 'cat < <(echo abc)')
+[ "$res" == "abc" ] || err $LINENO
+
+res=$($com <<< 'f () { cat $1 ; } ; f <(seq 2)')
+[ "$res" == "1
+2" ] || err $LINENO
+
+res=$($com <<< 'echo abc | tee >(tr a-z A-Z > /tmp/rusty_bash_ps) > /dev/null ; cat /tmp/rusty_bash_ps')
+[ "$res" == "ABC" ] || err $LINENO
+
+res=$(timeout 5 $com <<< 'tee >(wc -c) >(wc -l) <<< abc > /dev/null' | sort)
+[ "$res" == "1
+4" ] || err $LINENO
+
+if [ "$(uname)" = "Linux" ] ; then
+	res=$($com <<< 'cat <(ls /proc/self/fd | wc -l) <(ls /proc/self/fd | wc -l) | uniq | wc -l')
+	[ "$res" == "1" ] || err $LINENO
+fi
+
+if [ "$(uname)" = "Linux" ] ; then
+	res=$($com <<< 'a=(/proc/$$/fd/*) ; echo ${#a[@]} ; b=$(cat <(echo x)) ; a=(/proc/$$/fd/*) ; echo ${#a[@]}' | uniq | wc -l)
+	[ "$res" == "1" ] || err $LINENO
+fi
+
 # array
 
 res=$($com <<< 'A=( a b ); echo ${A[1]}')
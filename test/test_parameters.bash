@@ -52,6 +52,9 @@ res=$($com <<< 'declare -A A; A[aaa]=bbb ;A[ccc]=ddd ; echo ${A[@]}')
 res=$($com <<< 'B=ccc; declare -A A; A[aaa]=bbb ;A[ccc]=ddd ; echo ${A[$B]}')
 [ "$res" == "ddd" ] || err $LINENO
 
+res=$($com <<< 'declare -A A; A[x y]=1; A[1+1]=2; k=1; A["q"]=3; echo "${A[x y]}" ${A[1+1]} ${A[$k+1]} ${A[q]}')
+[ "$res" == "1 2 2 3" ] || err $LINENO
+
 ### FUNCNAME ###
 
 res=$($com <<< 'f(){ g () { echo ${FUNCNAME[@]} ;} ; g ;} ; f')
@@ -68,6 +71,46 @@ res=$($com -c 'set a b ; A=1;echo ${!A}')
 res=$($com -c ' A=@@; echo ${!A}')
 [[ "$?" -eq 1 ]] || err $LINENO
 
+### NAME AND KEY ENUMERATION ###
+
+res=$($com -c 'BUILD_A=1; BUILD_B=2; BUILDX=3; BUILD_F(){ :; }; echo ${!BUILD_*}')
+[[ "$res" == "BUILD_A BUILD_B" ]] || err $LINENO
+
+res=$($com -c 'BUILD_A=1; BUILD_B=2; for n in "${!BUILD_@}"; do echo "[$n]"; done')
+[[ "$res" == "[BUILD_A]
+[BUILD_B]" ]] || err $LINENO
+
+res=$($com -c 'BUILD_A=1; BUILD_B=2; for n in "${!BUILD_*}"; do echo "[$n]"; done')
+[[ "$res" == "[BUILD_A BUILD_B]" ]] || err $LINENO
+
+res=$($com -c 'echo ${!NOBUILD_*}end')
+[[ "$res" == "end" ]] || err $LINENO
+
+res=$($com -c 'A=(x y); A[5]=z; echo ${!A[@]}')
+[[ "$res" == "0 1 5" ]] || err $LINENO
+
+res=$($com -c 'A=(x y); A[5]=z; for i in "${!A[@]}"; do echo "<$i>"; done')
+[[ "$res" == "<0>
+<1>
+<5>" ]] || err $LINENO
+
+res=$($com -c 'A=(x y); A[5]=z; for i in "${!A[*]}"; do echo "<$i>"; done')
+[[ "$res" == "<0 1 5>" ]] || err $LINENO
+
+res=$($com -c 'declare -A A; A[aaa]=1; A[bbb]=2; for k in "${!A[@]}"; do echo $k=${A[$k]}; done | sort')
+[[ "$res" == "aaa=1
+bbb=2" ]] || err $LINENO
+
+res=$($com -c 'declare -A A; echo ${!A[@]}end')
+[[ "$res" == "end" ]] || err $LINENO
+
+res=$($com -c 'A=(x y z); B_1=1; B_2=2; IFS=,; echo "${!A[*]}" "${!A[@]}"; echo "${!B_*}" "${!B_@}"')
+[[ "$res" == "0,1,2 0 1 2
+B_1,B_2 B_1 B_2" ]] || err $LINENO
+
+res=$($com -c 's=v; echo "[${!s[@]}][${!s[*]}][${!u[@]}]"')
+[[ "$res" == "[0][0][]" ]] || err $LINENO
+
 
 ### PARTIAL POSITION PARAMETER ###
 
@@ -89,4 +132,49 @@ res=$($com <<< 'set a b ; A=("${@:1}") ; echo ${A[0]}')
 res=$($com <<< 'set a b c ; A=("${@:1:1}") ; echo ${A[0]}')
 [ "$res" == "a" ] || err $LINENO
 
+### IFS ###
+
+res=$($com <<< 'x="a,b,,c,"; IFS=,; for i in $x; do echo -n "[$i]"; done')
+[ "$res" == "[a][b][][c]" ] || err $LINENO
+
+res=$($com <<< 'IFS=,; x=",a"; set -- $x; echo $# "[$1]"; set -- pre$x; echo $# $1')
+[ "$res" == "2 []
+2 pre" ] || err $LINENO
+
+res=$($com <<< 'IFS=:; p="/bin:/usr/bin"; for d in $p; do echo $d; done')
+[ "$res" == "/bin
+/usr/bin" ] || err $LINENO
+
+res=$($com <<< 'IFS=" ,"; x=" a , b  c ,d "; set -- $x; echo $# "$*"')
+[ "$res" == "4 a b c d" ] || err $LINENO
+
+res=$($com <<< 'x=" a  b "; set -- $x; echo $#; IFS=; set -- $x; echo $#; unset IFS; set -- $x; echo $#')
+[ "$res" == "2
+1
+2" ] || err $LINENO
+
+res=$($com <<< 'set -- a "b c" ""; IFS=,; echo "$*" "${*}"; IFS=; echo "$*"; unset IFS; echo "$*"')
+[ "$res" == "a,b c, a,b c,
+ab c
+a b c " ] || err $LINENO
+
+res=$($com <<< 'set -- a "b c" ""; IFS=; set -- $*; echo $#; IFS=-; a=(1 "2 3"); echo "${a[*]}"; set -- ${a[@]}; echo $#')
+[ "$res" == "2
+1-2 3
+2" ] || err $LINENO
+
+res=$($com <<< 'f () { echo $#; }; f ${u:-a b}; IFS=,; f ${u:-a b}; f ${u:-a,b}; f "${u:-a,b}"; f a,b')
+[ "$res" == "2
+1
+2
+1
+1" ] || err $LINENO
+
+res=$($com <<< 'IFS=1; echo $((2110)); v=$(echo 3141); echo $v')
+[ "$res" == "2  0
+3 4" ] || err $LINENO
+
+res=$($com <<< 'IFS=,; v="a,b"; w=$v; echo "$w"')
+[ "$res" == "a,b" ] || err $LINENO
+
 echo $0 >> ./ok
@@ -114,6 +114,51 @@ res=$($com <<< 'ls aaaaaaaaaaaaaa 2>&1 | wc -l')
 #res=$($com <<< 'ls 200>&100')  <- not passed on macOS of GitHub Actions, 20241019
 #[ "$?" == "1" ] || err $LINENO
 
+# &>>
+
+res=$($com <<< 'echo a > /tmp/rusty_bash_o; ls aaaa &>> /tmp/rusty_bash_o; { echo b; } &>> /tmp/rusty_bash_o; cat /tmp/rusty_bash_o | wc -l | tr -dc 0-9')
+[ "$res" == "3" ] || err $LINENO
+
+# <&, >&-, <&-, n>&m-
+
+res=$($com <<< 'cat 3< /etc/passwd <&3 | wc -l')
+[ "$res" == "$(cat /etc/passwd | wc -l)" ] || err $LINENO
+
+res=$($com <<< 'ls aaaa 2>&- ; echo $?')
+[ "$res" == "2" ] || err $LINENO
+
+res=$($com <<< '{ echo a >&3 ; } 3>&1- ; echo b')
+[ "$res" == "a
+b" ] || err $LINENO
+
+res=$($com <<< 'cat <&- ; echo $?')
+[ "$res" == "1" ] || err $LINENO
+
+# <>, >|
+
+res=$($com <<< 'echo abc > /tmp/rusty_bash_o; cat <> /tmp/rusty_bash_o')
+[ "$res" == "abc" ] || err $LINENO
+
+res=$($com <<< 'echo abc > /tmp/rusty_bash_o; echo def >| /tmp/rusty_bash_o; cat /tmp/rusty_bash_o')
+[ "$res" == "def" ] || err $LINENO
+
+# {var}>
+
+res=$($com <<< 'cd . {fd}> /tmp/rusty_bash_o; [ $fd -ge 10 ] && echo ok; echo abc >&$fd; cd . {fd}>&- ; cat /tmp/rusty_bash_o')
+[ "$res" == "ok
+abc" ] || err $LINENO
+
+res=$($com <<< 'cd . {fd}> /tmp/rusty_bash_o; cd . {fd}>&- ; echo abc >&$fd')
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'exec {W[1]}> /tmp/rusty_bash_o; echo abc >&${W[1]}; echo ${#W[@]}; exec {W[1]}>&- ; cat /tmp/rusty_bash_o; echo x >&${W[1]}')
+[ "$?" == "1" ] || err $LINENO
+[ "$res" == "1
+abc" ] || err $LINENO
+
+res=$($com <<< 'declare -A H; echo abc > /tmp/rusty_bash_o; exec {H[k]}< /tmp/rusty_bash_o; read x <&${H[k]}; echo $x')
+[ "$res" == "abc" ] || err $LINENO
+
 # with expansion
 
 res=$($com <<< 'echo a > {a,b}' 2>&1)
@@ -138,5 +183,83 @@ if [ "$(uname)" = "Linux" ] ; then
 	[ "$res" == "1" ] || err $LINENO
 fi
 
+# heredoc
+
+res=$($com <<< 'a=3
+cat <<EOF
+x $a $((a+1)) $(echo y) \$a "q"
+EOF')
+[ "$res" == 'x 3 4 y $a "q"' ] || err $LINENO
+
+res=$($com <<< 'a=3
+cat <<"EOF"
+$a $(echo y)
+EOF')
+[ "$res" == '$a $(echo y)' ] || err $LINENO
+
+res=$($com <<< 'cat <<-EOF
+		abc
+	EOF
+echo ok')
+[ "$res" == 'abc
+ok' ] || err $LINENO
+
+res=$($com <<< 'cat <<A; echo $LINENO
+a
+A')
+[ "$res" == 'a
+1' ] || err $LINENO
+
+res=$($com <<< 'f () { cat <<A <<B
+a
+A
+b
+B
+}
+f')
+[ "$res" == "b" ] || err $LINENO
+
+res=$($com <<< 'while read a b ; do echo $b ; done <<EOF
+1 x
+2 y
+EOF')
+[ "$res" == 'x
+y' ] || err $LINENO
+
+res=$($com <<< 'cat <<EOF
+abc' 2>&1)
+[ "$res" == 'sush: warning: here-document at line 1 delimited by end-of-file (wanted `EOF'"')"'
+abc' ] || err $LINENO
+
+### EXEC ###
+
+res=$($com <<< 'exec 3>/tmp/rusty_bash1; echo a >&3; echo b >&3; exec 3>&-; echo c >&3; cat /tmp/rusty_bash1' 2>/dev/null)
+[ "$res" == 'a
+b' ] || err $LINENO
+
+res=$($com <<< 'exec {fd}>/tmp/rusty_bash1; echo x >&$fd; exec {fd}>&-; cat /tmp/rusty_bash1; [ $fd -ge 10 ]; echo $?')
+[ "$res" == 'x
+0' ] || err $LINENO
+
+res=$($com <<< 'rm -f /tmp/rusty_bash1; (exec >>/tmp/rusty_bash1 2>&1; echo out; ls /nonexist); wc -l < /tmp/rusty_bash1')
+[ "$res" == "2" ] || err $LINENO
+
+res=$($com <<< 'echo -e "1\n2" > /tmp/rusty_bash1; exec 4</tmp/rusty_bash1; read a <&4; read b <&4; echo $b$a')
+[ "$res" == "21" ] || err $LINENO
+
+res=$($com <<< 'exec echo end; echo never')
+[ "$res" == "end" ] || err $LINENO
+
+res=$($com <<< '(exec -a foo bash -c "echo \$0"); (exec -l bash -c "echo \$0")')
+[ "$res" == 'foo
+-bash' ] || err $LINENO
+
+res=$($com <<< 'A=1; export A; (exec -c env) | grep -c "^A="')
+[ "$res" == "0" ] || err $LINENO
+
+res=$($com <<< '(exec nosuch; echo no); echo $?; exec -x; echo $?; exec -a; echo $?' 2>/dev/null)
+[ "$res" == '127
+2
+2' ] || err $LINENO
 
 echo $0 >> ./ok
@@ -59,4 +59,18 @@ chmod +x $tmp-script
 res=$($tmp-script)
 [ "$res" = "4 4" ] || err $LINENO
 
+# redirections in a script must not overwrite the fd of the script
+
+{ echo "exec 3> $tmp-log"; seq -f 'echo %g' 2000; } > $tmp-script
+res=$($com $tmp-script | tail -n 1)
+[ "$res" = "2000" ] || err $LINENO
+
+echo 'echo NG' > $tmp-log
+{ echo "exec 3< $tmp-log"; echo 'echo a'; echo 'echo b'; } > $tmp-script
+res=$($com $tmp-script)
+[ "$res" = "a
+b" ] || err $LINENO
+
+rm -f $tmp-script $tmp-log
+
 echo $0 >> ./ok